@@ -0,0 +1,245 @@
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+
+/// A run of digits on a single row. `end` is exclusive.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct Number {
+    pub row: usize,
+    pub start: usize,
+    pub end: usize,
+    pub value: u32,
+}
+
+/// Any character that isn't a digit or a `.`. Columns count chars rather than bytes, so
+/// multi-byte symbols take up a single column like everything else.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct Symbol {
+    pub row: usize,
+    pub col: usize,
+    pub kind: char,
+}
+
+/// How many part numbers touch a kind of symbol, and their sum.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct SymbolStats {
+    pub kind: char,
+    pub count: usize,
+    pub sum: u32,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+enum Cell {
+    Number(usize),
+    Symbol(usize),
+}
+
+fn neighbours(row: usize, start: usize, end: usize) -> impl Iterator<Item = (usize, usize)> {
+    let cols = start.saturating_sub(1)..=end;
+    (row.saturating_sub(1)..=row + 1).flat_map(move |r| cols.clone().map(move |c| (r, c)))
+}
+
+pub struct EngineSchematic {
+    numbers: Vec<Number>,
+    symbols: Vec<Symbol>,
+    // Maps every occupied coordinate to the number or symbol covering it.
+    index: HashMap<(usize, usize), Cell>,
+}
+
+impl EngineSchematic {
+    pub fn parse(lines: impl IntoIterator<Item = impl AsRef<str>>) -> Self {
+        let mut numbers: Vec<Number> = Vec::new();
+        let mut symbols: Vec<Symbol> = Vec::new();
+        let mut index = HashMap::new();
+
+        for (row, line) in lines.into_iter().enumerate() {
+            let mut current: Option<Number> = None;
+
+            for (col, char) in line.as_ref().chars().enumerate() {
+                if let Some(digit) = char.to_digit(10) {
+                    let number = current.get_or_insert(Number {
+                        row,
+                        start: col,
+                        end: col,
+                        value: 0,
+                    });
+                    number.end = col + 1;
+                    number.value = 10 * number.value + digit;
+                    index.insert((row, col), Cell::Number(numbers.len()));
+                    continue;
+                }
+
+                numbers.extend(current.take());
+                if char != '.' {
+                    index.insert((row, col), Cell::Symbol(symbols.len()));
+                    symbols.push(Symbol {
+                        row,
+                        col,
+                        kind: char,
+                    });
+                }
+            }
+            numbers.extend(current);
+        }
+
+        Self {
+            numbers,
+            symbols,
+            index,
+        }
+    }
+
+    pub fn numbers(&self) -> &[Number] {
+        &self.numbers
+    }
+
+    pub fn symbols(&self) -> &[Symbol] {
+        &self.symbols
+    }
+
+    /// Numbers touching the given symbol, including diagonally.
+    pub fn numbers_adjacent_to(&self, symbol: &Symbol) -> Vec<&Number> {
+        let mut ids: Vec<usize> = neighbours(symbol.row, symbol.col, symbol.col + 1)
+            .filter_map(|coord| match self.index.get(&coord) {
+                Some(&Cell::Number(id)) => Some(id),
+                _ => None,
+            })
+            .collect();
+        // A number can cover several neighbouring cells.
+        ids.dedup();
+
+        ids.into_iter().map(|id| &self.numbers[id]).collect()
+    }
+
+    /// Symbols touching the given number, including diagonally.
+    pub fn symbols_adjacent_to(&self, number: &Number) -> Vec<&Symbol> {
+        neighbours(number.row, number.start, number.end)
+            .filter_map(|coord| match self.index.get(&coord) {
+                Some(&Cell::Symbol(id)) => Some(&self.symbols[id]),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Numbers adjacent to at least one symbol.
+    pub fn part_numbers(&self) -> impl Iterator<Item = &Number> {
+        self.numbers
+            .iter()
+            .filter(|number| !self.symbols_adjacent_to(number).is_empty())
+    }
+
+    /// Symbols of the given kind which touch exactly `n` numbers, along with those numbers.
+    pub fn gears(&self, kind: char, n: usize) -> impl Iterator<Item = (&Symbol, Vec<&Number>)> {
+        self.symbols
+            .iter()
+            .filter(move |symbol| symbol.kind == kind)
+            .map(|symbol| (symbol, self.numbers_adjacent_to(symbol)))
+            .filter(move |(_, adj)| adj.len() == n)
+    }
+
+    /// Per-kind statistics, sorted by symbol. A number touching two symbols of the same kind is
+    /// only counted once for that kind.
+    pub fn symbol_report(&self) -> Vec<SymbolStats> {
+        let mut stats: BTreeMap<char, SymbolStats> = BTreeMap::new();
+
+        for number in &self.numbers {
+            let kinds: BTreeSet<char> = self
+                .symbols_adjacent_to(number)
+                .into_iter()
+                .map(|symbol| symbol.kind)
+                .collect();
+
+            for kind in kinds {
+                let entry = stats.entry(kind).or_insert(SymbolStats {
+                    kind,
+                    count: 0,
+                    sum: 0,
+                });
+                entry.count += 1;
+                entry.sum += number.value;
+            }
+        }
+
+        stats.into_values().collect()
+    }
+}
+
+pub fn solution1(lines: impl IntoIterator<Item = impl AsRef<str>>) -> u32 {
+    let schematic = EngineSchematic::parse(lines);
+    schematic.part_numbers().map(|number| number.value).sum()
+}
+
+pub fn solution2(lines: impl IntoIterator<Item = impl AsRef<str>>) -> u32 {
+    let schematic = EngineSchematic::parse(lines);
+    schematic
+        .gears('*', 2)
+        .map(|(_, adj)| adj.iter().map(|number| number.value).product::<u32>())
+        .sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn part_1() {
+        let input = include_str!("../test.txt").lines();
+        assert_eq!(4361, solution1(input));
+    }
+
+    #[test]
+    fn part_2() {
+        let input = include_str!("../test.txt").lines();
+        assert_eq!(467_835, solution2(input));
+    }
+
+    #[test]
+    fn test_adjacency() {
+        let schematic = EngineSchematic::parse(include_str!("../test.txt").lines());
+
+        let star = schematic.symbols()[0];
+        let adj: Vec<u32> = schematic
+            .numbers_adjacent_to(&star)
+            .iter()
+            .map(|number| number.value)
+            .collect();
+        assert_eq!(vec![467, 35], adj);
+
+        let number = schematic.numbers()[0];
+        assert_eq!(vec![&star], schematic.symbols_adjacent_to(&number));
+        assert!(schematic
+            .symbols_adjacent_to(&schematic.numbers()[1])
+            .is_empty());
+    }
+
+    #[test]
+    fn test_unicode() {
+        let lines = ["12€..", "..µ34", "5...×"];
+        let schematic = EngineSchematic::parse(lines);
+
+        let spans: Vec<(usize, usize, usize)> = schematic
+            .numbers()
+            .iter()
+            .map(|number| (number.row, number.start, number.end))
+            .collect();
+        assert_eq!(vec![(0, 0, 2), (1, 3, 5), (2, 0, 1)], spans);
+        assert_eq!(4, schematic.symbols()[2].col);
+        assert_eq!(12 + 34, solution1(lines));
+    }
+
+    #[test]
+    fn test_symbol_report() {
+        let schematic = EngineSchematic::parse(include_str!("../test.txt").lines());
+        let report = schematic.symbol_report();
+
+        let kinds: String = report.iter().map(|stats| stats.kind).collect();
+        assert_eq!("#$*+", kinds);
+        assert_eq!(
+            SymbolStats {
+                kind: '*',
+                count: 5,
+                sum: 467 + 35 + 617 + 755 + 598,
+            },
+            report[2]
+        );
+        assert_eq!(4361, report.iter().map(|stats| stats.sum).sum::<u32>());
+    }
+}
@@ -1,42 +1,182 @@
-fn parse_ints(line: &[u8]) -> u128 {
-    line.chunks_exact(3).map(|digits| {
-        10 * (digits[1] & 0xf) + (digits[2] & 0xf)
-    }).fold(0, |bit_set, n| bit_set | (1 << n))
+use std::cmp::Ordering;
+use std::str::FromStr;
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Card {
+    pub id: u32,
+    pub winning: Vec<u64>,
+    pub mine: Vec<u64>,
+}
+
+fn parse_ints(nums: &str) -> Vec<u64> {
+    nums.split_ascii_whitespace()
+        .map(|s| s.parse().unwrap())
+        .collect()
+}
+
+fn bit_set(nums: &[u64]) -> u128 {
+    nums.iter().fold(0, |bit_set, n| bit_set | (1 << n))
 }
 
-pub fn solutions(lines: &[impl AsRef<str>]) -> (u32, u32) {
-    let mut sum = 0;
-    let mut copies: Vec<u32> = vec![1; lines.len()];
-
-    // These are the same across the whole file
-    let (colon_idx, pipe_idx) = {
-        let line = lines[0].as_ref().as_bytes();
-        let colon_idx = line.iter().position(|&c| c == b':').unwrap();
-        let pipe_idx = line.iter().position(|&c| c == b'|').unwrap();
-
-        (colon_idx, pipe_idx)
-    };
-
-    lines.iter().map(|line| {
-        let line = line.as_ref().as_bytes();
-        let winning = &line[colon_idx + 1..pipe_idx];
-        let my_nums = &line[pipe_idx + 1..];
-
-        (parse_ints(winning) & parse_ints(my_nums)).count_ones()
-    }).enumerate().for_each(|(idx, score)| {
-        let reps = copies[idx];
-        match score {
-            0 => {}
-            n => {
-                sum += 1 << (n - 1);
-                for copy in copies.iter_mut().skip(idx + 1).take(n.try_into().unwrap()) {
-                    *copy += reps;
+fn sorted(nums: &[u64]) -> Vec<u64> {
+    let mut nums = nums.to_vec();
+    nums.sort_unstable();
+    nums.dedup();
+    nums
+}
+
+impl Card {
+    pub fn parse(line: &str) -> Self {
+        let (header, body) = line.split_once(':').unwrap();
+        let (winning, mine) = body.split_once('|').unwrap();
+        let id = header.trim_start_matches("Card").trim().parse().unwrap();
+
+        Self {
+            id,
+            winning: parse_ints(winning),
+            mine: parse_ints(mine),
+        }
+    }
+
+    /// Number of distinct numbers on the card which are also winning numbers.
+    pub fn matches(&self) -> u32 {
+        let fits_bit_set = self.winning.iter().chain(&self.mine).all(|&n| n < 128);
+        if fits_bit_set {
+            return (bit_set(&self.winning) & bit_set(&self.mine)).count_ones();
+        }
+
+        // Otherwise walk both sorted lists together.
+        let winning = sorted(&self.winning);
+        let mine = sorted(&self.mine);
+        let (mut i, mut j, mut count) = (0, 0, 0);
+        while i < winning.len() && j < mine.len() {
+            match winning[i].cmp(&mine[j]) {
+                Ordering::Less => i += 1,
+                Ordering::Greater => j += 1,
+                Ordering::Equal => {
+                    count += 1;
+                    i += 1;
+                    j += 1;
                 }
             }
-        };
-    });
+        }
+        count
+    }
+}
+
+/// Points awarded for a card's matches.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Scoring {
+    /// 1, 2, 4, 8, ...
+    Doubling,
+    /// 1, 2, 3, 4, ...
+    Linear,
+    /// 1, 1, 2, 3, 5, ...
+    Fibonacci,
+}
+
+impl Scoring {
+    /// `None` once the score no longer fits in a `u64`, from 65 matches when doubling and 94
+    /// with Fibonacci.
+    pub fn score(self, matches: u32) -> Option<u64> {
+        match (self, matches) {
+            (_, 0) => Some(0),
+            (Self::Doubling, n) => 1_u64.checked_shl(n - 1),
+            (Self::Linear, n) => Some(n.into()),
+            (Self::Fibonacci, n) => {
+                let (mut a, mut b) = (0_u64, 1_u64);
+                for _ in 1..n {
+                    (a, b) = (b, a.checked_add(b)?);
+                }
+                Some(b)
+            }
+        }
+    }
+}
+
+impl FromStr for Scoring {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "doubling" => Ok(Self::Doubling),
+            "linear" => Ok(Self::Linear),
+            "fibonacci" => Ok(Self::Fibonacci),
+            _ => Err(format!("unknown scoring rule: {s}")),
+        }
+    }
+}
 
-    (sum, copies.into_iter().sum())
+/// How a card ended up with the copies it has.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct CardTrace {
+    pub id: u32,
+    pub matches: u32,
+    /// Total instances of this card, including the original.
+    pub copies: u64,
+    /// Earlier cards which won copies of this one, with how many copies each gave.
+    pub contributors: Vec<(u32, u64)>,
+}
+
+/// Propagate copies through the cards, keeping track of where each copy came from.
+///
+/// `None` if any card ends up with more copies than fit in a `u64`.
+pub fn cascade(cards: &[Card]) -> Option<Vec<CardTrace>> {
+    let mut traces: Vec<CardTrace> = cards
+        .iter()
+        .map(|card| CardTrace {
+            id: card.id,
+            matches: card.matches(),
+            copies: 1,
+            contributors: vec![],
+        })
+        .collect();
+
+    for idx in 0..traces.len() {
+        let CardTrace {
+            id,
+            matches,
+            copies,
+            ..
+        } = traces[idx];
+        for trace in traces
+            .iter_mut()
+            .skip(idx + 1)
+            .take(matches.try_into().unwrap())
+        {
+            trace.copies = trace.copies.checked_add(copies)?;
+            trace.contributors.push((id, copies));
+        }
+    }
+
+    Some(traces)
+}
+
+/// `None` if any card's score, or the total, doesn't fit in a `u64`.
+pub fn total_score(traces: &[CardTrace], scoring: Scoring) -> Option<u64> {
+    traces.iter().try_fold(0_u64, |total, trace| {
+        total.checked_add(scoring.score(trace.matches)?)
+    })
+}
+
+/// `None` if the total number of cards doesn't fit in a `u64`.
+pub fn total_copies(traces: &[CardTrace]) -> Option<u64> {
+    traces
+        .iter()
+        .try_fold(0_u64, |total, trace| total.checked_add(trace.copies))
+}
+
+pub fn solutions(lines: &[impl AsRef<str>]) -> (u64, u64) {
+    let cards: Vec<Card> = lines
+        .iter()
+        .map(|line| Card::parse(line.as_ref()))
+        .collect();
+    let traces = cascade(&cards).expect("copies overflow a u64");
+
+    (
+        total_score(&traces, Scoring::Doubling).expect("score overflows a u64"),
+        total_copies(&traces).expect("copies overflow a u64"),
+    )
 }
 
 #[cfg(test)]
@@ -70,4 +210,125 @@ mod tests {
 
         assert_eq!(30, solutions(&lines).1);
     }
+
+    #[test]
+    fn test_irregular_layout() {
+        let lines = [
+            "Card 1: 41 48 83 86 17 | 83 86 6 31 17 9 48 53",
+            "Card  2:13 32 20 16 61|61 30 68 82 17 32 24 19",
+            "Card 3: 1 21 53 59 44 7 | 69 82 63 72 16 21 14 1",
+            "Card 4: 41 92 73 84 69 | 59 84 76 51 58 5 54 83",
+            "Card 5: 1087 83 26 28 32 | 88 30 70 12 93 22 82 36",
+            "Card 6: 31 18 13 56 72 | 74 77 10 23 35 67 36 11",
+        ];
+
+        assert_eq!((13, 30), solutions(&lines));
+    }
+
+    #[test]
+    fn test_large_numbers() {
+        let card = Card::parse("Card 1: 1000 128 3 99999999999 | 3 3 128 99999999999 7");
+        assert_eq!(1, card.id);
+        assert_eq!(3, card.matches());
+    }
+
+    fn example() -> Vec<Card> {
+        [
+            "Card 1: 41 48 83 86 17 | 83 86  6 31 17  9 48 53",
+            "Card 2: 13 32 20 16 61 | 61 30 68 82 17 32 24 19",
+            "Card 3:  1 21 53 59 44 | 69 82 63 72 16 21 14  1",
+            "Card 4: 41 92 73 84 69 | 59 84 76 51 58  5 54 83",
+            "Card 5: 87 83 26 28 32 | 88 30 70 12 93 22 82 36",
+            "Card 6: 31 18 13 56 72 | 74 77 10 23 35 67 36 11",
+        ]
+        .into_iter()
+        .map(Card::parse)
+        .collect()
+    }
+
+    #[test]
+    fn test_cascade() {
+        let traces = cascade(&example()).unwrap();
+
+        let copies: Vec<u64> = traces.iter().map(|trace| trace.copies).collect();
+        assert_eq!(vec![1, 2, 4, 8, 14, 1], copies);
+        assert_eq!(vec![(1, 1), (3, 4), (4, 8)], traces[4].contributors);
+        assert!(traces[5].contributors.is_empty());
+    }
+
+    #[test]
+    fn test_scoring() {
+        let traces = cascade(&example()).unwrap();
+
+        assert_eq!(Some(13), total_score(&traces, Scoring::Doubling));
+        assert_eq!(Some(4 + 2 + 2 + 1), total_score(&traces, Scoring::Linear));
+        assert_eq!(
+            Some(3 + 1 + 1 + 1),
+            total_score(&traces, Scoring::Fibonacci)
+        );
+        let fibs: Vec<u64> = (0..8)
+            .map(|n| Scoring::Fibonacci.score(n).unwrap())
+            .collect();
+        assert_eq!(vec![0, 1, 1, 2, 3, 5, 8, 13], fibs);
+        assert_eq!(Ok(Scoring::Linear), "linear".parse());
+    }
+
+    #[test]
+    fn test_score_overflow() {
+        assert_eq!(Some(1 << 63), Scoring::Doubling.score(64));
+        assert_eq!(None, Scoring::Doubling.score(65));
+        assert_eq!(
+            Some(12_200_160_415_121_876_738),
+            Scoring::Fibonacci.score(93)
+        );
+        assert_eq!(None, Scoring::Fibonacci.score(94));
+        assert_eq!(Some(u64::from(u32::MAX)), Scoring::Linear.score(u32::MAX));
+
+        // A card wide enough to reach the limit.
+        let winning: Vec<String> = (1..=65).map(|n| n.to_string()).collect();
+        let line = format!("Card 1: {} | {}", winning.join(" "), winning.join(" "));
+        let traces = cascade(&[Card::parse(&line)]).unwrap();
+        assert_eq!(None, total_score(&traces, Scoring::Doubling));
+        assert_eq!(Some(65), total_score(&traces, Scoring::Linear));
+
+        // Each score fits but the total doesn't.
+        let line = format!(
+            "Card 1: {} | {}",
+            winning[..64].join(" "),
+            winning[..64].join(" ")
+        );
+        let cards = [
+            Card::parse(&line),
+            Card::parse(&line.replace("Card 1", "Card 2")),
+        ];
+        let traces = cascade(&cards).unwrap();
+        assert_eq!(None, total_score(&traces, Scoring::Doubling));
+    }
+
+    #[test]
+    fn test_copies_overflow() {
+        // Every card wins a copy of every later one, so each card has as many copies as all the
+        // cards before it put together, plus its original.
+        let cards = |count: u32| -> Vec<Card> {
+            let numbers: Vec<String> = (1..=count).map(|n| n.to_string()).collect();
+            let numbers = numbers.join(" ");
+            (1..=count)
+                .map(|id| Card::parse(&format!("Card {id}: {numbers} | {numbers}")))
+                .collect()
+        };
+
+        let traces = cascade(&cards(40)).unwrap();
+        assert_eq!(1 << 39, traces[39].copies);
+        assert_eq!(Some((1 << 40) - 1), total_copies(&traces));
+
+        let traces = cascade(&cards(64)).unwrap();
+        assert_eq!(1 << 63, traces[63].copies);
+        assert_eq!(Some(u64::MAX), total_copies(&traces));
+        assert_eq!(None, cascade(&cards(65)));
+
+        // Each card's copies fit but the total doesn't.
+        let mut traces = cascade(&cards(64)).unwrap();
+        traces.push(traces[0].clone());
+        assert_eq!(None, total_copies(&traces));
+    }
 }
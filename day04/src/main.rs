@@ -1,12 +1,29 @@
+use day4::{cascade, total_copies, total_score, Card, Scoring};
+use std::env;
 use std::io::{self, BufRead};
-use day4::solutions;
 
 fn main() {
+    let scoring: Scoring = env::args()
+        .nth(1)
+        .map_or(Scoring::Doubling, |arg| arg.parse().unwrap());
+
     let stdin = io::stdin();
-    let lines: Vec<String> = stdin.lock().lines().map(Result::unwrap).collect();
+    let cards: Vec<Card> = stdin
+        .lock()
+        .lines()
+        .map(|line| Card::parse(&line.unwrap()))
+        .collect();
+    let Some(traces) = cascade(&cards) else {
+        println!("The copies overflow a u64");
+        return;
+    };
 
-    let (p1, p2) = solutions(&lines);
-    println!("Part 1: {p1}");
-    println!("Part 2: {p2}");
+    match total_score(&traces, scoring) {
+        Some(p1) => println!("Part 1: {p1}"),
+        None => println!("Part 1: overflows a u64"),
+    }
+    match total_copies(&traces) {
+        Some(p2) => println!("Part 2: {p2}"),
+        None => println!("Part 2: overflows a u64"),
+    }
 }
-
@@ -0,0 +1,203 @@
+use std::cmp;
+
+/// Half-open range of ids, `start..end`.
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Copy)]
+pub struct Interval {
+    pub start: usize,
+    pub end: usize,
+}
+
+impl Interval {
+    pub fn new(start: usize, end: usize) -> Self {
+        Self { start, end }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.start >= self.end
+    }
+
+    pub fn len(&self) -> usize {
+        self.end.saturating_sub(self.start)
+    }
+
+    pub fn contains(&self, id: usize) -> bool {
+        self.start <= id && id < self.end
+    }
+
+    pub fn intersection(&self, other: &Interval) -> Option<Interval> {
+        let interval = Interval::new(
+            cmp::max(self.start, other.start),
+            cmp::min(self.end, other.end),
+        );
+        (!interval.is_empty()).then_some(interval)
+    }
+}
+
+/// A set of ids stored as sorted, disjoint, non-touching, non-empty intervals.
+#[derive(Debug, Default, PartialEq, Eq, Clone)]
+pub struct IntervalSet {
+    intervals: Vec<Interval>,
+}
+
+impl IntervalSet {
+    pub fn new() -> Self {
+        Self { intervals: vec![] }
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &Interval> {
+        self.intervals.iter()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.intervals.is_empty()
+    }
+
+    pub fn contains(&self, id: usize) -> bool {
+        let idx = self
+            .intervals
+            .partition_point(|interval| interval.end <= id);
+        self.intervals
+            .get(idx)
+            .is_some_and(|interval| interval.contains(id))
+    }
+
+    /// Smallest id in the set.
+    pub fn min(&self) -> Option<usize> {
+        self.intervals.first().map(|interval| interval.start)
+    }
+
+    pub fn union(&self, other: &IntervalSet) -> IntervalSet {
+        self.iter().chain(other.iter()).copied().collect()
+    }
+
+    pub fn intersection(&self, other: &IntervalSet) -> IntervalSet {
+        let mut out = vec![];
+        let (mut i, mut j) = (0, 0);
+        while i < self.intervals.len() && j < other.intervals.len() {
+            let (a, b) = (&self.intervals[i], &other.intervals[j]);
+            out.extend(a.intersection(b));
+            // Whichever ends first can't intersect anything else.
+            if a.end < b.end {
+                i += 1;
+            } else {
+                j += 1;
+            }
+        }
+        Self { intervals: out }
+    }
+
+    pub fn difference(&self, other: &IntervalSet) -> IntervalSet {
+        let mut out = vec![];
+        let mut j = 0;
+        for interval in &self.intervals {
+            let mut start = interval.start;
+            // Skip holes which end before this interval does anything.
+            while j < other.intervals.len() && other.intervals[j].end <= start {
+                j += 1;
+            }
+            let mut k = j;
+            while k < other.intervals.len() && other.intervals[k].start < interval.end {
+                let hole = &other.intervals[k];
+                if start < hole.start {
+                    out.push(Interval::new(start, hole.start));
+                }
+                start = cmp::max(start, hole.end);
+                k += 1;
+            }
+            if start < interval.end {
+                out.push(Interval::new(start, interval.end));
+            }
+        }
+        Self { intervals: out }
+    }
+}
+
+impl From<Interval> for IntervalSet {
+    fn from(interval: Interval) -> Self {
+        std::iter::once(interval).collect()
+    }
+}
+
+impl IntoIterator for IntervalSet {
+    type Item = Interval;
+    type IntoIter = std::vec::IntoIter<Interval>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.intervals.into_iter()
+    }
+}
+
+impl FromIterator<Interval> for IntervalSet {
+    /// Sort intervals and join any which overlap or touch.
+    fn from_iter<T: IntoIterator<Item = Interval>>(iter: T) -> Self {
+        let mut intervals: Vec<Interval> = iter
+            .into_iter()
+            .filter(|interval| !interval.is_empty())
+            .collect();
+        intervals.sort_unstable();
+
+        let mut out: Vec<Interval> = vec![];
+        for interval in intervals {
+            match out.last_mut() {
+                Some(last) if interval.start <= last.end => {
+                    last.end = cmp::max(last.end, interval.end);
+                }
+                _ => out.push(interval),
+            }
+        }
+        Self { intervals: out }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const LIMIT: usize = 8;
+
+    /// Every set of ids below `LIMIT`, as a bitmask alongside the matching `IntervalSet`.
+    fn all_sets() -> impl Iterator<Item = (u32, IntervalSet)> {
+        (0..1 << LIMIT).map(|mask: u32| {
+            let set = (0..LIMIT)
+                .filter(|id| mask & (1 << id) != 0)
+                .map(|id| Interval::new(id, id + 1))
+                .collect();
+            (mask, set)
+        })
+    }
+
+    fn to_mask(set: &IntervalSet) -> u32 {
+        (0..LIMIT)
+            .filter(|&id| set.contains(id))
+            .fold(0, |mask, id| mask | (1 << id))
+    }
+
+    #[test]
+    fn test_set_operations() {
+        let sets: Vec<(u32, IntervalSet)> = all_sets().collect();
+        for (a, set_a) in &sets {
+            for (b, set_b) in &sets {
+                assert_eq!(a | b, to_mask(&set_a.union(set_b)));
+                assert_eq!(a & b, to_mask(&set_a.intersection(set_b)));
+                assert_eq!(a & !b, to_mask(&set_a.difference(set_b)));
+            }
+        }
+    }
+
+    #[test]
+    fn test_normalised() {
+        let set: IntervalSet = [
+            Interval::new(5, 7),
+            Interval::new(0, 2),
+            Interval::new(3, 3),
+            Interval::new(2, 4),
+            Interval::new(6, 9),
+        ]
+        .into_iter()
+        .collect();
+
+        let expected = vec![Interval::new(0, 4), Interval::new(5, 9)];
+        assert_eq!(expected, set.iter().copied().collect::<Vec<_>>());
+        assert_eq!(Some(0), set.min());
+    }
+}
@@ -1,27 +1,43 @@
-use std::cmp;
+use std::{
+    cmp, fmt,
+    num::{IntErrorKind, ParseIntError},
+};
 
-#[derive(Debug, PartialEq, Eq)]
-struct MapEntry {
+mod interval;
+
+pub use interval::{Interval, IntervalSet};
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct MapEntry {
     pub start: usize,
     pub end: usize,
     pub dest: usize,
 }
 impl MapEntry {
-    pub fn parse(line: &str) -> Self {
-        let mut nums = line
+    pub fn parse(line: &str) -> Result<Self, AlmanacError> {
+        let nums = line
             .split_ascii_whitespace()
-            .map(|s| s.parse::<usize>().unwrap());
-        let dest = nums.next().unwrap();
-        let start = nums.next().unwrap();
-        let len = nums.next().unwrap();
+            .map(|s| parse_number(s, line, AlmanacError::BadEntry))
+            .collect::<Result<Vec<_>, _>>()?;
+        let [dest, start, len] = nums[..] else {
+            return Err(AlmanacError::BadEntry(line.to_string()));
+        };
 
-        Self {
-            start,
-            dest,
-            end: start + len,
+        // Both ranges have to fit, otherwise mapping the end of the source would overflow.
+        match (start.checked_add(len), dest.checked_add(len)) {
+            (Some(end), Some(_)) => Ok(Self { start, dest, end }),
+            _ => Err(AlmanacError::Overflow(line.to_string())),
         }
     }
 
+    pub fn source(&self) -> Interval {
+        Interval::new(self.start, self.end)
+    }
+
+    pub fn image(&self) -> Interval {
+        Interval::new(self.dest, self.dest + (self.end - self.start))
+    }
+
     pub fn map(&self, input: usize) -> Option<usize> {
         if input < self.start || self.end <= input {
             return None;
@@ -31,7 +47,8 @@ impl MapEntry {
     }
 }
 
-struct Map {
+#[derive(Debug, Default, PartialEq, Eq, Clone)]
+pub struct Map {
     entries: Vec<MapEntry>,
 }
 
@@ -40,130 +57,483 @@ impl Map {
         Self { entries: vec![] }
     }
 
+    /// Adds an entry, keeping the entries sorted by source start.
     pub fn add(&mut self, entry: MapEntry) {
-        self.entries.push(entry);
+        let idx = self.entries.partition_point(|e| e.start <= entry.start);
+        self.entries.insert(idx, entry);
     }
 
-    pub fn sort(&mut self) {
-        self.entries.sort_by_key(|e| e.start);
+    /// Index of the first entry which doesn't end at or before `id`. Requires sorted entries.
+    fn first_entry_after(&self, id: usize) -> usize {
+        self.entries.partition_point(|entry| entry.end <= id)
     }
 
-    fn map_id(&self, id: usize) -> usize {
-        for e in &self.entries {
-            if let Some(id) = e.map(id) {
-                return id;
+    pub fn map_id(&self, id: usize) -> usize {
+        self.entries
+            .get(self.first_entry_after(id))
+            .and_then(|entry| entry.map(id))
+            .unwrap_or(id)
+    }
+
+    pub fn map_interval(&self, interval: Interval) -> IntervalSet {
+        let mut sources = vec![];
+        let mut images = vec![];
+
+        for entry in self.entries[self.first_entry_after(interval.start)..]
+            .iter()
+            .take_while(|entry| entry.start < interval.end)
+        {
+            if let Some(source) = interval.intersection(&entry.source()) {
+                sources.push(source);
+                images.push(Interval::new(
+                    entry.dest + (source.start - entry.start),
+                    entry.dest + (source.end - entry.start),
+                ));
             }
         }
-        id
+
+        // Whatever isn't covered by an entry maps to itself.
+        let sources: IntervalSet = sources.into_iter().collect();
+        let images: IntervalSet = images.into_iter().collect();
+        IntervalSet::from(interval)
+            .difference(&sources)
+            .union(&images)
     }
 
-    pub fn map_interval(&self, interval: (usize, usize)) -> Vec<(usize, usize)> {
+    /// Pairs of entries whose source ranges overlap.
+    pub fn overlaps(&self) -> Vec<(MapEntry, MapEntry)> {
+        let mut entries = self.entries.clone();
+        entries.sort_by_key(|e| e.start);
+
         let mut out = vec![];
-        let mut remaining = interval;
-        let mut start = interval.0;
-        let mut end = 0;
+        for (i, first) in entries.iter().enumerate() {
+            for second in entries[i + 1..]
+                .iter()
+                .take_while(|second| second.start < first.end)
+            {
+                out.push((*first, *second));
+            }
+        }
+        out
+    }
 
-        self.entries
+    /// The entries of this map with the identity gaps between them filled in, so that every id
+    /// is covered by exactly one entry.
+    fn total_entries(&self) -> Vec<MapEntry> {
+        let mut out = vec![];
+        let mut prev_end = 0;
+        for entry in &self.entries {
+            if prev_end < entry.start {
+                out.push(MapEntry {
+                    start: prev_end,
+                    end: entry.start,
+                    dest: prev_end,
+                });
+            }
+            out.push(*entry);
+            prev_end = entry.end;
+        }
+        if prev_end < usize::MAX {
+            out.push(MapEntry {
+                start: prev_end,
+                end: usize::MAX,
+                dest: prev_end,
+            });
+        }
+        out
+    }
+
+    /// A single map equivalent to applying `self` and then `next`.
+    pub fn compose(&self, next: &Map) -> Map {
+        let next_entries = next.total_entries();
+        let mut entries: Vec<MapEntry> = vec![];
+
+        for entry in self.total_entries() {
+            let Interval {
+                start: image_start,
+                end: image_end,
+            } = entry.image();
+
+            let first = next_entries.partition_point(|e| e.end <= image_start);
+            for e in next_entries[first..]
+                .iter()
+                .take_while(|e| e.start < image_end)
+            {
+                let start = cmp::max(image_start, e.start);
+                let end = cmp::min(image_end, e.end);
+                let piece = MapEntry {
+                    start: entry.start + (start - image_start),
+                    end: entry.start + (end - image_start),
+                    dest: e.dest + (start - e.start),
+                };
+
+                // Merge with the previous piece when the two line up.
+                match entries.last_mut() {
+                    Some(last) if last.end == piece.start && last.image().end == piece.dest => {
+                        last.end = piece.end;
+                    }
+                    _ => entries.push(piece),
+                }
+            }
+        }
+
+        // Identity pieces don't need to be stored.
+        entries.retain(|entry| entry.start != entry.dest);
+        Map { entries }
+    }
+
+    /// All ids which this map sends into `interval`.
+    pub fn preimage_interval(&self, interval: Interval) -> IntervalSet {
+        self.total_entries()
+            .into_iter()
+            .filter_map(|entry| {
+                interval.intersection(&entry.image()).map(|image| {
+                    Interval::new(
+                        entry.start + (image.start - entry.dest),
+                        entry.start + (image.end - entry.dest),
+                    )
+                })
+            })
+            .collect()
+    }
+
+    /// All ids which this map sends to `id`.
+    pub fn preimage_id(&self, id: usize) -> Vec<usize> {
+        // Neighbouring preimages would be joined in an `IntervalSet`, so go entry by entry.
+        let mut ids: Vec<usize> = self
+            .total_entries()
             .iter()
-            .skip_while(|entry| entry.end < interval.0)
-            .take_while(|entry| interval.1 >= entry.start)
-            .for_each(|entry| {
-                debug_assert_ne!(remaining.0, remaining.1);
-
-                start = cmp::max(remaining.0, entry.start);
-                if start > remaining.0 {
-                    // handle remainder
-                    out.push((remaining.0, start));
+            .filter(|entry| entry.image().contains(id))
+            .map(|entry| entry.start + (id - entry.dest))
+            .collect();
+        ids.sort_unstable();
+        ids
+    }
+}
+
+impl fmt::Display for Map {
+    /// One row per entry in the same `dest start len` layout as the almanac.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for entry in &self.entries {
+            writeln!(
+                f,
+                "{} {} {}",
+                entry.dest,
+                entry.start,
+                entry.end - entry.start
+            )?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum AlmanacError {
+    /// A first line which isn't `seeds:` followed by numbers.
+    BadSeeds(String),
+    /// A map title which isn't of the form `<source>-to-<dest> map:`.
+    BadTitle(String),
+    /// A map entry which isn't three numbers, or which comes before any map title.
+    BadEntry(String),
+    /// A category with more than one map leading out of it.
+    Branch(String),
+    /// A map whose source isn't the destination of the map before it.
+    OutOfOrder {
+        expected: String,
+        found: String,
+    },
+    /// A map leading back to a category earlier in the chain.
+    Cycle(String),
+    UnknownCategory(String),
+    Unreachable {
+        from: String,
+        to: String,
+    },
+    /// Two entries in the same map whose source ranges overlap.
+    Overlap {
+        category: String,
+        first: MapEntry,
+        second: MapEntry,
+    },
+    /// A line with a number, or a seed or map range, which doesn't fit in a `usize`.
+    Overflow(String),
+}
+
+impl fmt::Display for AlmanacError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::BadSeeds(line) => write!(f, "bad seeds: {line:?}"),
+            Self::BadTitle(title) => write!(f, "bad map title: {title:?}"),
+            Self::BadEntry(line) => write!(f, "bad map entry: {line:?}"),
+            Self::Branch(category) => write!(f, "more than one map out of {category}"),
+            Self::OutOfOrder { expected, found } => {
+                write!(f, "expected a map from {expected}, found one from {found}")
+            }
+            Self::Cycle(category) => write!(f, "maps loop back to {category}"),
+            Self::UnknownCategory(category) => write!(f, "unknown category {category}"),
+            Self::Unreachable { from, to } => write!(f, "{to} can't be reached from {from}"),
+            Self::Overlap {
+                category,
+                first,
+                second,
+            } => write!(
+                f,
+                "{category} map has overlapping entries {}..{} and {}..{}",
+                first.start, first.end, second.start, second.end
+            ),
+            Self::Overflow(line) => write!(f, "range overflows: {line:?}"),
+        }
+    }
+}
+
+impl std::error::Error for AlmanacError {}
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Almanac {
+    pub seeds: Vec<usize>,
+    /// Maps in chain order, so `maps[i]` goes from `categories[i]` to `categories[i + 1]`.
+    pub maps: Vec<Map>,
+    pub categories: Vec<String>,
+}
+
+impl Almanac {
+    pub fn parse(lines: impl IntoIterator<Item = impl AsRef<str>>) -> Result<Self, AlmanacError> {
+        let mut lines = lines.into_iter();
+        let seeds_line = lines.next();
+        let seeds = get_seeds(seeds_line.as_ref().map_or("", AsRef::as_ref))?;
+
+        let mut maps = vec![];
+        let mut categories: Vec<String> = vec![];
+
+        for (source, dest, map) in generate_maps(lines)? {
+            // Every category but the last already has a map out of it.
+            if categories
+                .iter()
+                .rev()
+                .skip(1)
+                .any(|category| *category == source)
+            {
+                return Err(AlmanacError::Branch(source));
+            }
+            match categories.last() {
+                Some(expected) if *expected != source => {
+                    return Err(AlmanacError::OutOfOrder {
+                        expected: expected.clone(),
+                        found: source,
+                    });
                 }
+                Some(_) => {}
+                None => categories.push(source.clone()),
+            }
+            if categories.contains(&dest) {
+                return Err(AlmanacError::Cycle(dest));
+            }
+            if let Some(&(first, second)) = map.overlaps().first() {
+                return Err(AlmanacError::Overlap {
+                    category: source,
+                    first,
+                    second,
+                });
+            }
 
-                end = cmp::min(remaining.1, entry.end);
-                remaining.0 = end;
-                out.push((
-                    start + entry.dest - entry.start,
-                    end + entry.dest - entry.start,
-                ));
-            });
-        if remaining.0 != remaining.1 {
-            out.push(remaining);
+            categories.push(dest);
+            maps.push(map);
         }
 
-        if out.is_empty() {
-            vec![interval]
-        } else {
-            out.sort_unstable();
-            out
+        Ok(Self {
+            seeds,
+            maps,
+            categories,
+        })
+    }
+
+    fn position(&self, category: &str) -> Option<usize> {
+        self.categories.iter().position(|c| c == category)
+    }
+
+    /// Categories which can be reached from `from`, in the order they're reached.
+    pub fn reachable(&self, from: &str) -> Vec<&str> {
+        self.position(from).map_or(vec![], |idx| {
+            self.categories[idx + 1..]
+                .iter()
+                .map(String::as_str)
+                .collect()
+        })
+    }
+
+    /// A single map taking ids in category `from` to category `to`.
+    pub fn map_between(&self, from: &str, to: &str) -> Result<Map, AlmanacError> {
+        let [start, end] = [from, to].map(|category| {
+            self.position(category)
+                .ok_or_else(|| AlmanacError::UnknownCategory(category.to_string()))
+        });
+        let (start, end) = (start?, end?);
+        if end < start {
+            return Err(AlmanacError::Unreachable {
+                from: from.to_string(),
+                to: to.to_string(),
+            });
         }
+
+        Ok(self.maps[start..end]
+            .iter()
+            .fold(Map::new(), |composed, map| composed.compose(map)))
+    }
+
+    /// Every map in the chain composed into one.
+    pub fn composed(&self) -> Map {
+        self.maps
+            .iter()
+            .fold(Map::new(), |composed, map| composed.compose(map))
+    }
+
+    /// Seeds which end up at a location inside `interval`, found by walking the chain backwards.
+    pub fn preimage(&self, interval: Interval) -> IntervalSet {
+        self.maps
+            .iter()
+            .rev()
+            .fold(IntervalSet::from(interval), |set, map| {
+                set.iter()
+                    .flat_map(|&interval| map.preimage_interval(interval))
+                    .collect()
+            })
+    }
+
+    /// Part 2's seed ranges.
+    pub fn seed_ranges(&self) -> IntervalSet {
+        self.seeds
+            .chunks_exact(2)
+            .map(|chunk| Interval::new(chunk[0], chunk[0] + chunk[1]))
+            .collect()
     }
 }
 
-fn get_seeds(seeds_line: &str) -> Vec<usize> {
-    let no_header = seeds_line.split_once(':').unwrap().1;
-    no_header
-        .split_whitespace()
-        .map(|s| s.parse::<usize>().unwrap())
-        .collect()
+/// Parses a number from `line`, which is reported with `invalid` if it isn't one.
+fn parse_number(
+    s: &str,
+    line: &str,
+    invalid: fn(String) -> AlmanacError,
+) -> Result<usize, AlmanacError> {
+    s.parse().map_err(|err: ParseIntError| match err.kind() {
+        IntErrorKind::PosOverflow => AlmanacError::Overflow(line.to_string()),
+        _ => invalid(line.to_string()),
+    })
 }
 
-fn generate_maps(lines: impl IntoIterator<Item = impl AsRef<str>>) -> Vec<Map> {
-    let mut lines = lines.into_iter();
+fn get_seeds(seeds_line: &str) -> Result<Vec<usize>, AlmanacError> {
+    let no_header = seeds_line
+        .strip_prefix("seeds:")
+        .ok_or_else(|| AlmanacError::BadSeeds(seeds_line.to_string()))?;
+    let seeds = no_header
+        .split_whitespace()
+        .map(|s| parse_number(s, seeds_line, AlmanacError::BadSeeds))
+        .collect::<Result<Vec<_>, _>>()?;
 
-    // Pop empty line.
-    lines.next();
+    // Checked here so that `seed_ranges` can't overflow.
+    if seeds
+        .chunks_exact(2)
+        .any(|chunk| chunk[0].checked_add(chunk[1]).is_none())
+    {
+        return Err(AlmanacError::Overflow(seeds_line.to_string()));
+    }
+    Ok(seeds)
+}
 
-    let mut maps: Vec<Map> = Vec::new();
+fn parse_title(title: &str) -> Result<(String, String), AlmanacError> {
+    title
+        .strip_suffix(" map:")
+        .and_then(|names| names.split_once("-to-"))
+        .map(|(source, dest)| (source.to_string(), dest.to_string()))
+        .ok_or_else(|| AlmanacError::BadTitle(title.to_string()))
+}
+
+/// Each map along with its source and destination category.
+fn generate_maps(
+    lines: impl IntoIterator<Item = impl AsRef<str>>,
+) -> Result<Vec<(String, String, Map)>, AlmanacError> {
+    let mut maps: Vec<(String, String, Map)> = Vec::new();
 
-    lines.next(); // Consume title
-    maps.push(Map::new());
-    while let Some(line) = lines.next() {
-        if line.as_ref().is_empty() {
-            lines.next(); // Consume title
-            maps.push(Map::new());
+    for line in lines {
+        let line = line.as_ref();
+        if line.is_empty() {
+            continue;
+        }
+        if line.ends_with(':') {
+            let (source, dest) = parse_title(line)?;
+            maps.push((source, dest, Map::new()));
             continue;
         }
-        let map_entry = MapEntry::parse(line.as_ref());
-        let idx = maps.len() - 1;
-        maps[idx].add(map_entry);
+        let map_entry = MapEntry::parse(line)?;
+        let Some((_, _, map)) = maps.last_mut() else {
+            return Err(AlmanacError::BadEntry(line.to_string()));
+        };
+        map.add(map_entry);
     }
 
-    maps.iter_mut().for_each(Map::sort);
-
-    maps
+    Ok(maps)
 }
 
 pub fn solution1(lines: impl IntoIterator<Item = impl AsRef<str>>) -> usize {
-    let mut lines = lines.into_iter();
-    let seeds_line = lines.next().unwrap();
-    let seeds = get_seeds(seeds_line.as_ref());
-
-    let maps = generate_maps(lines);
+    let almanac = Almanac::parse(lines).unwrap();
+    let composed = almanac.composed();
 
-    seeds
+    almanac
+        .seeds
         .into_iter()
-        .map(|s| maps.iter().fold(s, |s, map| map.map_id(s)))
+        .map(|s| composed.map_id(s))
         .min()
         .unwrap()
 }
 
 pub fn solution2(lines: impl IntoIterator<Item = impl AsRef<str>>) -> usize {
-    let mut lines = lines.into_iter();
-    let seeds_line = lines.next().unwrap();
-    let seeds = get_seeds(seeds_line.as_ref());
+    let almanac = Almanac::parse(lines).unwrap();
+    let composed = almanac.composed();
 
-    let maps = generate_maps(lines);
+    almanac
+        .seed_ranges()
+        .iter()
+        .filter_map(|&seeds| composed.map_interval(seeds).min())
+        .min()
+        .unwrap()
+}
 
-    seeds
-        .chunks_exact(2)
-        .map(|chunk| {
-            let interval = (chunk[0], chunk[0] + chunk[1]);
-            let intervals = maps.iter().fold(vec![interval], |intervals, map| {
-                intervals
-                    .into_iter()
-                    .flat_map(|interval| map.map_interval(interval))
-                    .collect()
-            });
-            intervals.into_iter().map(|i| i.0).min().unwrap()
+/// Part 2 solved backwards: scan location ranges upwards until one of them comes from a seed.
+pub fn solution2_inverse(lines: impl IntoIterator<Item = impl AsRef<str>>) -> usize {
+    let almanac = Almanac::parse(lines).unwrap();
+    let composed = almanac.composed();
+    let seeds = almanac.seed_ranges();
+
+    // The composed map is linear between these locations.
+    let mut bounds: Vec<usize> = composed
+        .total_entries()
+        .iter()
+        .flat_map(|entry| [entry.image().start, entry.image().end])
+        .collect();
+    bounds.sort_unstable();
+    bounds.dedup();
+
+    bounds
+        .windows(2)
+        .find_map(|window| {
+            let window = Interval::new(window[0], window[1]);
+            // Pieces from different entries can land in the same window, so each one is checked
+            // on its own. Merging them would lose where each piece's smallest location is.
+            composed
+                .total_entries()
+                .iter()
+                .filter_map(|entry| {
+                    let image = window.intersection(&entry.image())?;
+                    let piece = Interval::new(
+                        entry.start + (image.start - entry.dest),
+                        entry.start + (image.end - entry.dest),
+                    );
+                    IntervalSet::from(piece)
+                        .intersection(&seeds)
+                        .min()
+                        .map(|seed| composed.map_id(seed))
+                })
+                .min()
         })
-        .min()
         .unwrap()
 }
 
@@ -177,12 +547,294 @@ mod tests {
         map.add(MapEntry {
             start: 10,
             end: 20,
-            dest: 50,
+            dest: 150,
         });
 
-        let intervals = map.map_interval((0, 100));
-        let expected: Vec<(usize, usize)> = vec![(0, 10), (20, 100), (50, 60)];
+        let intervals = map.map_interval(Interval::new(0, 100));
+        let expected: Vec<Interval> = vec![
+            Interval::new(0, 10),
+            Interval::new(20, 100),
+            Interval::new(150, 160),
+        ];
+
+        assert_eq!(expected, intervals.into_iter().collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_add_out_of_order() {
+        let mut map = Map::new();
+        map.add(MapEntry::parse("0 20 5").unwrap());
+        map.add(MapEntry::parse("70 5 10").unwrap());
+        map.add(MapEntry::parse("50 0 5").unwrap());
+
+        assert_eq!("50 0 5\n70 5 10\n0 20 5\n", map.to_string());
+        assert_eq!(52, map.map_id(2));
+        assert_eq!(77, map.map_id(12));
+        assert_eq!(15, map.map_id(15));
+        assert_eq!(4, map.map_id(24));
+    }
+
+    #[test]
+    fn test_compose_agrees_with_chain() {
+        for text in [include_str!("../example.txt"), include_str!("../input.txt")] {
+            let almanac = Almanac::parse(text.lines()).unwrap();
+            let composed = almanac.composed();
+
+            let mut ids: Vec<usize> = (0..200).collect();
+            ids.extend(almanac.seeds.iter().copied());
+            // Probe either side of every breakpoint in the chain.
+            for map in &almanac.maps {
+                for entry in &map.entries {
+                    for id in [entry.start, entry.end, entry.dest] {
+                        ids.extend([id.saturating_sub(1), id, id + 1]);
+                    }
+                }
+            }
+
+            for id in ids {
+                let stepwise = almanac.maps.iter().fold(id, |id, map| map.map_id(id));
+                assert_eq!(stepwise, composed.map_id(id), "{id}");
+            }
+        }
+    }
+
+    #[test]
+    fn test_composed_table() {
+        let almanac = Almanac::parse(include_str!("../example.txt").lines()).unwrap();
+        let table = almanac.maps[0].compose(&almanac.maps[1]).to_string();
+
+        // seed-to-soil then soil-to-fertilizer
+        let expected = "39 0 15\n0 15 35\n37 50 2\n54 52 46\n35 98 2\n";
+        assert_eq!(expected, table);
+    }
+
+    #[test]
+    fn test_preimage() {
+        let almanac = Almanac::parse(include_str!("../example.txt").lines()).unwrap();
+        let composed = almanac.composed();
+
+        let target = Interval::new(40, 60);
+        let preimage = almanac.preimage(target);
+        assert_eq!(preimage, composed.preimage_interval(target));
+        for id in 0..200 {
+            let location = composed.map_id(id);
+            assert_eq!(target.contains(location), preimage.contains(id), "{id}");
+        }
+
+        // Seed 98 is sent to soil 50, while seed 50 itself moves on to soil 52.
+        let map = &almanac.maps[0];
+        assert_eq!(vec![98], map.preimage_id(50));
+        assert_eq!(vec![50], map.preimage_id(52));
+        assert_eq!(vec![0], map.preimage_id(0));
+
+        // 4 is sent to 5 and 5 is left alone, so both preimages touch.
+        let mut map = Map::new();
+        map.add(MapEntry::parse("5 4 1").unwrap());
+        assert_eq!(vec![4, 5], map.preimage_id(5));
+        assert_eq!(Vec::<usize>::new(), map.preimage_id(4));
+    }
+
+    #[test]
+    fn test_solution2_inverse() {
+        for text in [include_str!("../example.txt"), include_str!("../input.txt")] {
+            assert_eq!(solution2(text.lines()), solution2_inverse(text.lines()));
+        }
+
+        // Seeds 0..10 and 10.. both land in locations 10..20, but only the second piece reaches
+        // location 10.
+        let text = "seeds: 5 10\n\nseed-to-location map:\n10 0 10";
+        assert_eq!(10, solution2(text.lines()));
+        assert_eq!(10, solution2_inverse(text.lines()));
+    }
+
+    #[test]
+    fn test_map_between() {
+        let almanac = Almanac::parse(include_str!("../example.txt").lines()).unwrap();
+
+        assert_eq!(
+            vec![
+                "soil",
+                "fertilizer",
+                "water",
+                "light",
+                "temperature",
+                "humidity",
+                "location"
+            ],
+            almanac.reachable("seed")
+        );
+        let soil_to_humidity = almanac.map_between("soil", "humidity").unwrap();
+        for id in 0..200 {
+            let stepwise = almanac.maps[1..6].iter().fold(id, |id, map| map.map_id(id));
+            assert_eq!(stepwise, soil_to_humidity.map_id(id));
+        }
+        assert_eq!(
+            almanac.composed(),
+            almanac.map_between("seed", "location").unwrap()
+        );
+
+        assert_eq!(
+            Err(AlmanacError::Unreachable {
+                from: "water".to_string(),
+                to: "soil".to_string()
+            }),
+            almanac.map_between("water", "soil")
+        );
+        assert_eq!(
+            Err(AlmanacError::UnknownCategory("fuel".to_string())),
+            almanac.map_between("seed", "fuel")
+        );
+    }
+
+    #[test]
+    fn test_bad_almanacs() {
+        let parse = |text: &str| Almanac::parse(text.lines());
+
+        let out_of_order = "seeds: 1\n\nseed-to-soil map:\n1 2 3\n\nwater-to-light map:\n1 2 3";
+        assert_eq!(
+            Err(AlmanacError::OutOfOrder {
+                expected: "soil".to_string(),
+                found: "water".to_string()
+            }),
+            parse(out_of_order)
+        );
+
+        let branch = "seeds: 1\n\nseed-to-soil map:\n\nsoil-to-water map:\n\nsoil-to-light map:";
+        assert_eq!(Err(AlmanacError::Branch("soil".to_string())), parse(branch));
+
+        let cycle = "seeds: 1\n\nseed-to-soil map:\n\nsoil-to-seed map:";
+        assert_eq!(Err(AlmanacError::Cycle("seed".to_string())), parse(cycle));
+
+        let bad_title = "seeds: 1\n\nseed to soil:";
+        assert_eq!(
+            Err(AlmanacError::BadTitle("seed to soil:".to_string())),
+            parse(bad_title)
+        );
+    }
+
+    /// Every map with up to two entries whose ranges lie below 8.
+    fn small_maps() -> Vec<Map> {
+        let mut entries = vec![];
+        for start in 0..6 {
+            for len in 1..4 {
+                for dest in 0..6 {
+                    entries.push(MapEntry {
+                        start,
+                        end: start + len,
+                        dest,
+                    });
+                }
+            }
+        }
+
+        let mut maps = vec![Map::new()];
+        for (i, a) in entries.iter().enumerate() {
+            maps.push(Map { entries: vec![*a] });
+            for b in entries[i + 1..].iter().filter(|b| a.end <= b.start) {
+                maps.push(Map {
+                    entries: vec![*a, *b],
+                });
+            }
+        }
+        maps
+    }
+
+    #[test]
+    fn test_map_interval_matches_map_id() {
+        for map in small_maps() {
+            for start in 0..10 {
+                for end in start + 1..=10 {
+                    let interval = Interval::new(start, end);
+                    let image = map.map_interval(interval);
+                    let preimage = map.preimage_interval(interval);
+
+                    for id in 0..12 {
+                        let in_image = (start..end).any(|s| map.map_id(s) == id);
+                        assert_eq!(in_image, image.contains(id), "{map:?} {interval:?} {id}");
+                        assert_eq!(
+                            interval.contains(map.map_id(id)),
+                            preimage.contains(id),
+                            "{map:?} {interval:?} {id}"
+                        );
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_validation() {
+        let mut map = Map::new();
+        let entries = [
+            MapEntry::parse("50 0 10").unwrap(),
+            MapEntry::parse("0 20 5").unwrap(),
+            MapEntry::parse("70 5 20").unwrap(),
+        ];
+        entries.into_iter().for_each(|entry| map.add(entry));
+        assert_eq!(
+            vec![(entries[0], entries[2]), (entries[2], entries[1])],
+            map.overlaps()
+        );
+
+        let overlapping = "seeds: 1\n\nseed-to-soil map:\n50 0 10\n70 5 20";
+        assert_eq!(
+            Err(AlmanacError::Overlap {
+                category: "seed".to_string(),
+                first: entries[0],
+                second: entries[2],
+            }),
+            Almanac::parse(overlapping.lines())
+        );
+
+        let max = usize::MAX;
+        let line = format!("0 {max} 1");
+        assert_eq!(
+            Err(AlmanacError::Overflow(line.clone())),
+            MapEntry::parse(&line)
+        );
+        let line = format!("{max} 0 1");
+        assert_eq!(
+            Err(AlmanacError::Overflow(line.clone())),
+            MapEntry::parse(&line)
+        );
+        assert!(MapEntry::parse(&format!("0 {} 1", max - 1)).is_ok());
+
+        let line = "99999999999999999999999 0 1";
+        assert_eq!(
+            Err(AlmanacError::Overflow(line.to_string())),
+            MapEntry::parse(line)
+        );
+        let line = format!("seeds: {max} 1");
+        assert_eq!(
+            Err(AlmanacError::Overflow(line.clone())),
+            Almanac::parse([line.as_str()])
+        );
+        assert!(Almanac::parse([format!("seeds: {} 1 {max}", max - 1)]).is_ok());
+    }
+
+    #[test]
+    fn test_malformed_lines() {
+        for line in ["", "1 2", "1 2 3 4", "1 -2 3", "1 two 3"] {
+            assert_eq!(
+                Err(AlmanacError::BadEntry(line.to_string())),
+                MapEntry::parse(line)
+            );
+        }
 
-        assert_eq!(expected, intervals);
+        let parse = |text: &str| Almanac::parse(text.lines());
+        assert_eq!(
+            Err(AlmanacError::BadEntry("1 2 3".to_string())),
+            parse("seeds: 1\n\n1 2 3\n\nseed-to-soil map:")
+        );
+        assert_eq!(
+            Err(AlmanacError::BadEntry("1 2".to_string())),
+            parse("seeds: 1\n\nseed-to-soil map:\n1 2")
+        );
+        assert_eq!(Err(AlmanacError::BadSeeds(String::new())), parse(""));
+        assert_eq!(Err(AlmanacError::BadSeeds("1 2".to_string())), parse("1 2"));
+        assert_eq!(
+            Err(AlmanacError::BadSeeds("seeds: 1 x".to_string())),
+            parse("seeds: 1 x")
+        );
     }
 }
@@ -1,4 +1,5 @@
-use day5::{solution1, solution2};
+use day5::{solution1, solution2, Almanac};
+use std::env;
 use std::io;
 use std::io::BufRead;
 
@@ -6,9 +7,19 @@ fn main() {
     let stdin = io::stdin();
     let lines: Vec<String> = stdin.lock().lines().map(Result::unwrap).collect();
 
+    let args: Vec<String> = env::args().skip(1).collect();
+    if args.first().map(String::as_str) == Some("table") {
+        let from = args.get(1).map_or("seed", String::as_str);
+        let to = args.get(2).map_or("location", String::as_str);
+        match Almanac::parse(&lines).and_then(|almanac| almanac.map_between(from, to)) {
+            Ok(map) => print!("{map}"),
+            Err(err) => eprintln!("{err}"),
+        }
+        return;
+    }
+
     let p1 = solution1(&lines);
     let p2 = solution2(&lines);
     println!("Part 1: {p1}");
     println!("Part 2: {p2}");
 }
-
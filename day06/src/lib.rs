@@ -1,31 +1,209 @@
+use std::ops::RangeInclusive;
+
 #[derive(Debug)]
 pub struct Race {
     pub time: u64,
     pub dist: u64,
 }
 
-fn score_race(race: &Race) -> u64 {
-    let t: f64 = race.time as f64;
-    let d: f64 = race.dist as f64;
-    let disc: f64 = (t * t - 4.0 * d).sqrt();
-    let min = (0.5 * (t - disc)).floor();
-    let max = (0.5 * (t + disc)).ceil();
+fn parse_row<'a>(line: &'a str, header: &str) -> impl Iterator<Item = &'a str> {
+    line.strip_prefix(header).unwrap().split_ascii_whitespace()
+}
+
+/// One race per column of the `Time:` and `Distance:` rows.
+pub fn parse_races<'a>(lines: impl IntoIterator<Item = &'a str>) -> Vec<Race> {
+    let mut lines = lines.into_iter();
+    let times = parse_row(lines.next().unwrap(), "Time:");
+    let dists = parse_row(lines.next().unwrap(), "Distance:");
+
+    times
+        .zip(dists)
+        .map(|(time, dist)| Race {
+            time: time.parse().unwrap(),
+            dist: dist.parse().unwrap(),
+        })
+        .collect()
+}
+
+/// A single race read with the spaces between numbers ignored.
+pub fn parse_kerned_race<'a>(lines: impl IntoIterator<Item = &'a str>) -> Race {
+    let mut lines = lines.into_iter();
+    let time: String = parse_row(lines.next().unwrap(), "Time:").collect();
+    let dist: String = parse_row(lines.next().unwrap(), "Distance:").collect();
 
-    max as u64 - min as u64 - 1
+    Race {
+        time: time.parse().unwrap(),
+        dist: dist.parse().unwrap(),
+    }
+}
+
+fn beats_record(race: &Race, hold: u128) -> bool {
+    hold * (u128::from(race.time) - hold) > race.dist.into()
+}
+
+/// Holds which beat the record, solved for directly. Everything is done in `u128` so that
+/// `time²` can't overflow and the square root is exact.
+fn closed_form_holds(race: &Race) -> Option<RangeInclusive<u64>> {
+    let t = u128::from(race.time);
+    let d = u128::from(race.dist);
+
+    // The best we can do is hold for half the time.
+    if t * t / 4 <= d {
+        return None;
+    }
+
+    // The lower root of hold * (t - hold) = d, rounded down to an integer, then nudged onto
+    // the first winning hold.
+    let mut hold = (t - (t * t - 4 * d).isqrt()) / 2;
+    while !beats_record(race, hold) {
+        hold += 1;
+    }
+    while hold > 0 && beats_record(race, hold - 1) {
+        hold -= 1;
+    }
+
+    // Winning holds are symmetric about t / 2.
+    let first = u64::try_from(hold).unwrap();
+    Some(first..=race.time - first)
+}
+
+/// Number of holds in `holds`. Holding for no time never wins, so this can't overflow.
+fn count(holds: Option<RangeInclusive<u64>>) -> u64 {
+    holds.map_or(0, |holds| holds.end() - holds.start() + 1)
+}
+
+/// How a boat behaves. The puzzle's boat gains 1mm/ms of speed per millisecond held, has no top
+/// speed and keeps its speed forever.
+#[derive(Debug, Clone, Copy)]
+pub struct BoatModel {
+    /// Speed gained per millisecond the button is held.
+    pub acceleration: u64,
+    pub max_speed: Option<u64>,
+    /// Speed lost every millisecond after the button is released, down to a stop.
+    pub friction: u64,
+}
+
+impl Default for BoatModel {
+    fn default() -> Self {
+        Self {
+            acceleration: 1,
+            max_speed: None,
+            friction: 0,
+        }
+    }
+}
+
+impl BoatModel {
+    /// Distance travelled in a race lasting `time` when holding the button for `hold`.
+    pub fn distance(&self, time: u64, hold: u64) -> u128 {
+        let speed = u128::from(self.acceleration).saturating_mul(hold.into());
+        let speed = self
+            .max_speed
+            .map_or(speed, |max_speed| speed.min(max_speed.into()));
+        let remaining = u128::from(time - hold);
+
+        if self.friction == 0 {
+            return speed.saturating_mul(remaining);
+        }
+
+        // Moves speed, speed - friction, ... until it stops or time runs out. That's an
+        // arithmetic series, and friction * (moving - 1) < speed, so only the final product can
+        // overflow. It saturates like the frictionless distance, which keeps the distance rising
+        // and then falling for `search`.
+        let friction = u128::from(self.friction);
+        let moving = remaining.min(speed.div_ceil(friction));
+        if moving == 0 {
+            return 0;
+        }
+        let last = speed - friction * (moving - 1);
+        if moving.is_multiple_of(2) {
+            (moving / 2).saturating_mul(speed.saturating_add(last))
+        } else {
+            // friction * (moving - 1) is even, so halving speed + last is exact.
+            moving.saturating_mul(last + friction * (moving - 1) / 2)
+        }
+    }
+
+    /// Number of ways to beat the record.
+    pub fn score(&self, race: &Race) -> u64 {
+        count(self.winning_holds(race))
+    }
+
+    /// The holds which beat the record, or `None` if none of them do. They're always a single
+    /// range, since the distance rises to a peak and then falls.
+    pub fn winning_holds(&self, race: &Race) -> Option<RangeInclusive<u64>> {
+        if self.friction == 0 && self.max_speed.is_none() {
+            if self.acceleration == 0 {
+                return None;
+            }
+            // acceleration * hold * (time - hold) > dist exactly when
+            // hold * (time - hold) > dist / acceleration.
+            return closed_form_holds(&Race {
+                time: race.time,
+                dist: race.dist / self.acceleration,
+            });
+        }
+
+        self.search(race)
+    }
+
+    /// Finds the winning holds by binary search, relying on the distance rising to a peak and
+    /// then falling as the hold gets longer.
+    fn search(&self, race: &Race) -> Option<RangeInclusive<u64>> {
+        let time = race.time;
+        let dist = u128::from(race.dist);
+        let distance = |hold: u64| self.distance(time, hold);
+
+        // First hold after which the distance starts dropping.
+        let peak = partition_point(0, time, |hold| distance(hold) <= distance(hold + 1));
+        if distance(peak) <= dist {
+            return None;
+        }
+
+        let first = partition_point(0, peak, |hold| distance(hold) <= dist);
+        // Holding for the whole race never wins, so it doesn't need checking.
+        let end = partition_point(peak, time, |hold| distance(hold) > dist);
+        Some(first..=end - 1)
+    }
+}
+
+/// Smallest value in `lo..hi` for which `pred` is false, assuming it's true then false.
+fn partition_point(mut lo: u64, mut hi: u64, pred: impl Fn(u64) -> bool) -> u64 {
+    while lo < hi {
+        let mid = lo + (hi - lo) / 2;
+        if pred(mid) {
+            lo = mid + 1;
+        } else {
+            hi = mid;
+        }
+    }
+    lo
 }
 
 pub fn solution1(races: &[Race]) -> u64 {
-    races.iter().map(score_race).product()
+    solution1_with(races, &BoatModel::default())
 }
 
 pub fn solution2(race: &Race) -> u64 {
-    score_race(race)
+    solution2_with(race, &BoatModel::default())
+}
+
+pub fn solution1_with(races: &[Race], model: &BoatModel) -> u64 {
+    races.iter().map(|race| model.score(race)).product()
+}
+
+pub fn solution2_with(race: &Race, model: &BoatModel) -> u64 {
+    model.score(race)
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    fn score_race(race: &Race) -> u64 {
+        count(closed_form_holds(race))
+    }
+
     #[test]
     fn part_1() {
         let races = vec![
@@ -40,4 +218,232 @@ mod tests {
         let p1 = solution1(&races);
         assert_eq!(288, p1);
     }
+
+    #[test]
+    fn test_parse() {
+        let input = "Time:      7  15   30\nDistance:  9  40  200";
+
+        let races = parse_races(input.lines());
+        assert_eq!(288, solution1(&races));
+
+        let race = parse_kerned_race(input.lines());
+        assert_eq!((71530, 940_200), (race.time, race.dist));
+        assert_eq!(71503, solution2(&race));
+    }
+
+    fn brute_force(race: &Race) -> u64 {
+        (0..=race.time)
+            .filter(|&hold| hold * (race.time - hold) > race.dist)
+            .count()
+            .try_into()
+            .unwrap()
+    }
+
+    #[test]
+    fn test_small_races() {
+        for time in 0..60 {
+            for dist in 0..=time * time / 4 + 1 {
+                let race = Race { time, dist };
+                assert_eq!(brute_force(&race), score_race(&race), "{race:?}");
+            }
+        }
+    }
+
+    #[test]
+    fn test_record_matched_exactly() {
+        // Holding for 10ms or 20ms exactly matches the record, so neither counts.
+        assert_eq!(
+            9,
+            score_race(&Race {
+                time: 30,
+                dist: 200
+            })
+        );
+        // Only the halfway hold ties.
+        assert_eq!(
+            0,
+            score_race(&Race {
+                time: 30,
+                dist: 225
+            })
+        );
+        assert_eq!(
+            1,
+            score_race(&Race {
+                time: 30,
+                dist: 224
+            })
+        );
+        assert_eq!(
+            0,
+            score_race(&Race {
+                time: 31,
+                dist: 240
+            })
+        );
+        assert_eq!(
+            2,
+            score_race(&Race {
+                time: 31,
+                dist: 239
+            })
+        );
+    }
+
+    #[test]
+    fn test_large_races() {
+        for (time, hold) in [(1 << 40, (1 << 20) + 1), (1 << 54, 511), (u64::MAX, 0)] {
+            // Set the record so that `hold` ties it, making `hold + 1` the first winner.
+            let dist = hold * (time - hold);
+            let expected = time - 2 * hold - 1;
+            assert_eq!(expected, score_race(&Race { time, dist }), "{time} {hold}");
+        }
+
+        // Too big to construct a tie, so check that the count lands on the boundary instead.
+        for time in [u64::MAX, u64::MAX - 1, 1 << 63] {
+            let race = Race {
+                time,
+                dist: u64::MAX,
+            };
+            let first = (u128::from(time) + 1 - u128::from(score_race(&race))) / 2;
+            assert!(beats_record(&race, first));
+            assert!(!beats_record(&race, first - 1));
+        }
+    }
+
+    #[test]
+    fn test_boat_models() {
+        let models = (0..3).flat_map(|acceleration| {
+            [None, Some(1), Some(4)]
+                .into_iter()
+                .flat_map(move |max_speed| {
+                    (0..3).map(move |friction| BoatModel {
+                        acceleration,
+                        max_speed,
+                        friction,
+                    })
+                })
+        });
+
+        for model in models {
+            for time in 0..25 {
+                for dist in 0..60 {
+                    let race = Race { time, dist };
+                    let winners: Vec<u64> = (0..=time)
+                        .filter(|&hold| model.distance(time, hold) > dist.into())
+                        .collect();
+                    let expected = winners.first().zip(winners.last());
+                    let holds = model.winning_holds(&race);
+                    assert_eq!(
+                        expected.map(|(&first, &last)| first..=last),
+                        holds,
+                        "{model:?} {race:?}"
+                    );
+                    assert_eq!(
+                        winners.len() as u64,
+                        model.score(&race),
+                        "{model:?} {race:?}"
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_friction_saturates() {
+        let model = BoatModel {
+            acceleration: u64::MAX,
+            max_speed: None,
+            friction: 1,
+        };
+        assert_eq!(u128::MAX, model.distance(u64::MAX, 1 << 40));
+        assert_eq!(0, model.distance(u64::MAX, u64::MAX));
+        assert_eq!(0, model.distance(u64::MAX, 0));
+
+        // Every hold but none and all of it goes further than any u64 record.
+        let race = Race {
+            time: u64::MAX,
+            dist: u64::MAX,
+        };
+        assert_eq!(u64::MAX - 1, model.score(&race));
+
+        // Exact right up to where the distance stops fitting.
+        let model = BoatModel {
+            acceleration: 1,
+            max_speed: None,
+            friction: 1,
+        };
+        let (speed, moving) = (1u128 << 63, (1u128 << 63) - 1);
+        let expected = moving * speed - moving * (moving - 1) / 2;
+        assert_eq!(expected, model.distance(u64::MAX, 1 << 63));
+    }
+
+    #[test]
+    fn test_default_model() {
+        let model = BoatModel::default();
+        assert_eq!(200, model.distance(30, 10));
+        assert_eq!(
+            Some(14..=71516),
+            model.search(&Race {
+                time: 71530,
+                dist: 940_200
+            })
+        );
+
+        let fast = BoatModel {
+            acceleration: 3,
+            ..model
+        };
+        assert_eq!(
+            fast.search(&Race {
+                time: 30,
+                dist: 601
+            }),
+            fast.winning_holds(&Race {
+                time: 30,
+                dist: 601
+            })
+        );
+    }
+
+    #[test]
+    fn test_winning_holds() {
+        let model = BoatModel::default();
+        // Holding for 10ms or 20ms exactly matches the record.
+        assert_eq!(
+            Some(11..=19),
+            model.winning_holds(&Race {
+                time: 30,
+                dist: 200
+            })
+        );
+        assert_eq!(Some(2..=5), model.winning_holds(&Race { time: 7, dist: 9 }));
+        assert_eq!(
+            Some(15..=15),
+            model.winning_holds(&Race {
+                time: 30,
+                dist: 224
+            })
+        );
+        assert_eq!(
+            None,
+            model.winning_holds(&Race {
+                time: 30,
+                dist: 225
+            })
+        );
+
+        // The search agrees with the closed form.
+        let race = Race {
+            time: 71530,
+            dist: 940_200,
+        };
+        assert_eq!(model.search(&race), model.winning_holds(&race));
+
+        let stalled = BoatModel {
+            acceleration: 0,
+            ..model
+        };
+        assert_eq!(None, stalled.winning_holds(&Race { time: 30, dist: 0 }));
+    }
 }
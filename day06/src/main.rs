@@ -1,30 +1,13 @@
-use day6::{solution1, solution2, Race};
+use day6::{parse_kerned_race, parse_races, solution1, solution2};
+use std::io::{self, Read};
 
 fn main() {
-    let races = vec![
-        Race {
-            time: 48,
-            dist: 296,
-        },
-        Race {
-            time: 93,
-            dist: 1928,
-        },
-        Race {
-            time: 85,
-            dist: 1236,
-        },
-        Race {
-            time: 95,
-            dist: 1391,
-        },
-    ];
+    let stdin = io::stdin();
+    let mut input: String = String::new();
+    stdin.lock().read_to_string(&mut input).unwrap();
 
-    let p1 = solution1(&races);
-    let p2 = solution2(&Race {
-        time: 48_93_85_95,
-        dist: 296_1928_1236_1391,
-    });
+    let p1 = solution1(&parse_races(input.lines()));
+    let p2 = solution2(&parse_kerned_race(input.lines()));
     println!("Part 1: {p1}");
     println!("Part 2: {p2}");
 }
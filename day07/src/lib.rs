@@ -0,0 +1,528 @@
+use std::cmp::Ordering;
+use std::fmt;
+
+/// Sizes of the groups of matching cards in a hand, largest first. Comparing these
+/// lexicographically ranks hands of any size, e.g. `[3, 2]` (a full house) beats `[3, 1, 1]`
+/// (three of a kind). A hand made only of wildcards has no groups at all.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct HandType {
+    groups: Vec<usize>,
+}
+
+impl HandType {
+    /// The type of a hand ignoring wildcards.
+    pub fn from_cards(cards: &[u8], is_wild: impl Fn(u8) -> bool) -> Self {
+        let mut cards: Vec<u8> = cards.iter().copied().filter(|&c| !is_wild(c)).collect();
+        cards.sort_unstable();
+
+        let mut groups: Vec<usize> = cards.chunk_by(|a, b| a == b).map(<[u8]>::len).collect();
+        groups.sort_unstable_by(|a, b| b.cmp(a));
+
+        Self { groups }
+    }
+
+    /// Every wildcard joins the largest group.
+    pub fn upgrade_with_wildcards(&self, num_wild: usize) -> Self {
+        let mut groups = self.groups.clone();
+        match groups.first_mut() {
+            Some(largest) => *largest += num_wild,
+            None if num_wild > 0 => groups.push(num_wild),
+            None => {}
+        }
+        Self { groups }
+    }
+
+    pub fn groups(&self) -> &[usize] {
+        &self.groups
+    }
+}
+
+impl fmt::Display for HandType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // Named by the largest groups, the same way whether or not wildcards are included.
+        match self.groups[..] {
+            [] => write!(f, "only jokers"),
+            [n, ..] if n > 5 => write!(f, "{n} of a kind"),
+            [5, ..] => write!(f, "five of a kind"),
+            [4, ..] => write!(f, "four of a kind"),
+            [3, 2, ..] => write!(f, "full house"),
+            [3, ..] => write!(f, "three of a kind"),
+            [2, 2, ..] => write!(f, "two pair"),
+            [2, ..] => write!(f, "one pair"),
+            _ => write!(f, "high card"),
+        }
+    }
+}
+
+/// How to order hands of the same type.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TieBreak {
+    /// Compare the first card, then the second, and so on.
+    CardByCard,
+    /// Compare the rank of the largest group, then the next largest, and so on.
+    GroupsByRank,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Hand {
+    pub hand_type: HandType,
+    /// Ranks of the cards, as positions in the deck.
+    pub cards: Vec<u8>,
+    pub bid: u32,
+}
+
+/// One line of a report.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct HandReport {
+    pub hand: Hand,
+    pub cards: String,
+    /// The hand's type before wildcards were used.
+    pub base_type: HandType,
+    pub rank: u32,
+    pub contribution: u32,
+}
+
+impl fmt::Display for HandReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{:>4}  {}  {} -> {}  bid {} wins {}",
+            self.rank,
+            self.cards,
+            self.base_type,
+            self.hand.hand_type,
+            self.hand.bid,
+            self.contribution
+        )
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum Reason {
+    HandType {
+        a: HandType,
+        b: HandType,
+    },
+    /// The first differing card, compared card by card.
+    Card {
+        position: usize,
+        a: char,
+        b: char,
+    },
+    /// The first differing group rank, compared largest group first.
+    Group {
+        position: usize,
+        a: char,
+        b: char,
+    },
+    Tie,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Explanation {
+    /// How the first hand compares to the second.
+    pub ordering: Ordering,
+    pub reason: Reason,
+}
+
+impl fmt::Display for Explanation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let verdict = match self.ordering {
+            Ordering::Less => "loses",
+            Ordering::Equal => "ties",
+            Ordering::Greater => "wins",
+        };
+        match &self.reason {
+            Reason::HandType { a, b } => write!(f, "{verdict}: {a} against {b}"),
+            Reason::Card { position, a, b } => {
+                write!(
+                    f,
+                    "{verdict}: same type, card {} is {a} against {b}",
+                    position + 1
+                )
+            }
+            Reason::Group { position, a, b } => write!(
+                f,
+                "{verdict}: same type, group {} is {a} against {b}",
+                position + 1
+            ),
+            Reason::Tie => write!(f, "{verdict}: the hands are equivalent"),
+        }
+    }
+}
+
+/// A variant of Camel Cards.
+#[derive(Debug, Clone)]
+pub struct Rules {
+    /// Card labels from weakest to strongest.
+    pub deck: Vec<char>,
+    pub hand_size: usize,
+    /// Labels of cards which can stand in for any other card.
+    pub wildcards: Vec<char>,
+    pub tie_break: TieBreak,
+}
+
+impl Rules {
+    pub fn part1() -> Self {
+        Self {
+            deck: "23456789TJQKA".chars().collect(),
+            hand_size: 5,
+            wildcards: vec![],
+            tie_break: TieBreak::CardByCard,
+        }
+    }
+
+    pub fn part2() -> Self {
+        Self {
+            deck: "J23456789TQKA".chars().collect(),
+            wildcards: vec!['J'],
+            ..Self::part1()
+        }
+    }
+
+    fn rank(&self, c: char) -> u8 {
+        let rank = self.deck.iter().position(|&d| d == c).unwrap();
+        rank.try_into().unwrap()
+    }
+
+    pub fn is_wild(&self, rank: u8) -> bool {
+        self.wildcards.contains(&self.deck[usize::from(rank)])
+    }
+
+    /// The type of a hand before wildcards are used.
+    pub fn base_type(&self, cards: &[u8]) -> HandType {
+        HandType::from_cards(cards, |c| self.is_wild(c))
+    }
+
+    /// The best type a hand can make, using the shortcut of adding every wildcard to the
+    /// largest group.
+    pub fn hand_type(&self, cards: &[u8]) -> HandType {
+        let num_wild = cards.iter().filter(|&&c| self.is_wild(c)).count();
+        self.base_type(cards).upgrade_with_wildcards(num_wild)
+    }
+
+    /// The best type a hand can make, found by trying every card in place of every wildcard.
+    pub fn best_type_exhaustive(&self, cards: &[u8]) -> HandType {
+        let substitutes: Vec<u8> = (0..self.deck.len())
+            .map(|rank| u8::try_from(rank).unwrap())
+            .filter(|&rank| !self.is_wild(rank))
+            .collect();
+        let mut cards = cards.to_vec();
+
+        self.best_substitution(&mut cards, 0, &substitutes)
+    }
+
+    fn best_substitution(&self, cards: &mut [u8], from: usize, substitutes: &[u8]) -> HandType {
+        let Some(idx) = (from..cards.len()).find(|&i| self.is_wild(cards[i])) else {
+            return HandType::from_cards(cards, |c| self.is_wild(c));
+        };
+
+        let wild = cards[idx];
+        let best = substitutes
+            .iter()
+            .map(|&rank| {
+                cards[idx] = rank;
+                self.best_substitution(cards, idx + 1, substitutes)
+            })
+            .max()
+            // Nothing to substitute, so the wildcard stays as it is.
+            .unwrap_or_else(|| {
+                let num_wild = cards.iter().filter(|&&c| self.is_wild(c)).count();
+                self.base_type(cards).upgrade_with_wildcards(num_wild)
+            });
+        cards[idx] = wild;
+
+        best
+    }
+
+    fn label(&self, rank: u8) -> char {
+        self.deck[usize::from(rank)]
+    }
+
+    pub fn labels(&self, hand: &Hand) -> String {
+        hand.cards.iter().map(|&c| self.label(c)).collect()
+    }
+
+    pub fn hand(&self, cards_str: &str, bid: u32) -> Hand {
+        let cards: Vec<u8> = cards_str.chars().map(|c| self.rank(c)).collect();
+        assert_eq!(self.hand_size, cards.len(), "{cards_str}");
+
+        Hand {
+            hand_type: self.hand_type(&cards),
+            cards,
+            bid,
+        }
+    }
+
+    pub fn parse_hand(&self, line: &str) -> Hand {
+        let (cards_str, bid_str) = line.split_once(' ').unwrap();
+        self.hand(cards_str, bid_str.parse().unwrap())
+    }
+
+    /// Ranks of the non-wild groups, largest group first and higher ranks first among groups
+    /// of the same size.
+    fn group_ranks(&self, cards: &[u8]) -> Vec<u8> {
+        let mut cards: Vec<u8> = cards
+            .iter()
+            .copied()
+            .filter(|&c| !self.is_wild(c))
+            .collect();
+        cards.sort_unstable();
+
+        let mut groups: Vec<(usize, u8)> = cards
+            .chunk_by(|a, b| a == b)
+            .map(|group| (group.len(), group[0]))
+            .collect();
+        groups.sort_unstable_by(|a, b| b.cmp(a));
+
+        groups.into_iter().map(|(_, rank)| rank).collect()
+    }
+
+    pub fn compare(&self, a: &Hand, b: &Hand) -> Ordering {
+        a.hand_type
+            .cmp(&b.hand_type)
+            .then_with(|| match self.tie_break {
+                TieBreak::CardByCard => a.cards.cmp(&b.cards),
+                TieBreak::GroupsByRank => {
+                    self.group_ranks(&a.cards).cmp(&self.group_ranks(&b.cards))
+                }
+            })
+    }
+
+    /// Why `a` and `b` compare the way they do.
+    pub fn explain(&self, a: &Hand, b: &Hand) -> Explanation {
+        let ordering = self.compare(a, b);
+        let first_difference = |a: &[u8], b: &[u8]| {
+            a.iter()
+                .zip(b)
+                .enumerate()
+                .find(|(_, (x, y))| x != y)
+                .map(|(position, (&x, &y))| (position, self.label(x), self.label(y)))
+        };
+
+        let reason = if a.hand_type != b.hand_type {
+            Reason::HandType {
+                a: a.hand_type.clone(),
+                b: b.hand_type.clone(),
+            }
+        } else {
+            let difference = match self.tie_break {
+                TieBreak::CardByCard => first_difference(&a.cards, &b.cards),
+                TieBreak::GroupsByRank => {
+                    first_difference(&self.group_ranks(&a.cards), &self.group_ranks(&b.cards))
+                }
+            };
+            match (self.tie_break, difference) {
+                (_, None) => Reason::Tie,
+                (TieBreak::CardByCard, Some((position, a, b))) => Reason::Card { position, a, b },
+                (TieBreak::GroupsByRank, Some((position, a, b))) => {
+                    Reason::Group { position, a, b }
+                }
+            }
+        };
+
+        Explanation { ordering, reason }
+    }
+
+    /// Every hand with how it was classified and what it won, from weakest to strongest.
+    pub fn report(&self, lines: impl IntoIterator<Item = impl AsRef<str>>) -> Vec<HandReport> {
+        self.ranked(lines)
+            .into_iter()
+            .enumerate()
+            .map(|(i, hand)| {
+                let rank = u32::try_from(i + 1).unwrap();
+                HandReport {
+                    cards: self.labels(&hand),
+                    base_type: self.base_type(&hand.cards),
+                    rank,
+                    contribution: hand.bid * rank,
+                    hand,
+                }
+            })
+            .collect()
+    }
+
+    /// Hands sorted from weakest to strongest.
+    pub fn ranked(&self, lines: impl IntoIterator<Item = impl AsRef<str>>) -> Vec<Hand> {
+        let mut hands: Vec<Hand> = lines
+            .into_iter()
+            .map(|line| self.parse_hand(line.as_ref()))
+            .collect();
+        hands.sort_by(|a, b| self.compare(a, b));
+        hands
+    }
+
+    pub fn winnings(&self, lines: impl IntoIterator<Item = impl AsRef<str>>) -> u32 {
+        self.ranked(lines)
+            .into_iter()
+            .enumerate()
+            .map(|(i, hand)| hand.bid * u32::try_from(i + 1).unwrap())
+            .sum()
+    }
+}
+
+pub fn solution(lines: impl IntoIterator<Item = impl AsRef<str>>, use_joker: bool) -> u32 {
+    let rules = if use_joker {
+        Rules::part2()
+    } else {
+        Rules::part1()
+    };
+    rules.winnings(lines)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn part_1() {
+        let input = include_str!("../example.txt").lines();
+        assert_eq!(6440, solution(input, false));
+    }
+
+    #[test]
+    fn part_2() {
+        let input = include_str!("../example.txt").lines();
+        assert_eq!(5905, solution(input, true));
+    }
+
+    #[test]
+    fn test_group_tie_break() {
+        let rules = Rules {
+            tie_break: TieBreak::GroupsByRank,
+            ..Rules::part1()
+        };
+
+        // Both full houses, but threes beat twos.
+        let a = rules.parse_hand("22333 1");
+        let b = rules.parse_hand("AA222 1");
+        assert_eq!(Ordering::Greater, rules.compare(&a, &b));
+        assert_eq!(Ordering::Less, Rules::part1().compare(&a, &b));
+    }
+
+    #[test]
+    fn test_larger_hands() {
+        let rules = Rules {
+            hand_size: 7,
+            wildcards: vec!['J', '2'],
+            ..Rules::part2()
+        };
+
+        let hand = rules.parse_hand("J2KKQQA 1");
+        assert_eq!(&[4, 2, 1], rules.hand_type(&hand.cards).groups());
+        assert_eq!(&[2, 2, 1], rules.base_type(&hand.cards).groups());
+        assert_eq!(
+            &[7],
+            rules
+                .hand_type(&rules.parse_hand("JJ2J22J 1").cards)
+                .groups()
+        );
+    }
+
+    #[test]
+    fn test_report() {
+        let report = Rules::part2().report(include_str!("../example.txt").lines());
+
+        let lines: Vec<String> = report.iter().map(ToString::to_string).collect();
+        assert_eq!(
+            "   1  32T3K  one pair -> one pair  bid 765 wins 765",
+            lines[0]
+        );
+        assert_eq!(
+            "   5  KTJJT  one pair -> four of a kind  bid 220 wins 1100",
+            lines[4]
+        );
+        assert_eq!(
+            5905,
+            report.iter().map(|line| line.contribution).sum::<u32>()
+        );
+    }
+
+    #[test]
+    fn test_explain() {
+        let rules = Rules::part2();
+
+        let a = rules.hand("JKKK2", 0);
+        let b = rules.hand("QQQQ2", 0);
+        let explanation = rules.explain(&a, &b);
+        assert_eq!(
+            Reason::Card {
+                position: 0,
+                a: 'J',
+                b: 'Q'
+            },
+            explanation.reason
+        );
+        assert_eq!(
+            "loses: same type, card 1 is J against Q",
+            explanation.to_string()
+        );
+
+        let explanation = rules.explain(&rules.hand("T55J5", 0), &rules.hand("KK677", 0));
+        assert_eq!(Ordering::Greater, explanation.ordering);
+        assert_eq!(
+            "wins: four of a kind against two pair",
+            explanation.to_string()
+        );
+
+        let groups = Rules {
+            tie_break: TieBreak::GroupsByRank,
+            ..Rules::part1()
+        };
+        let explanation = groups.explain(&groups.hand("2233A", 0), &groups.hand("A3322", 0));
+        assert_eq!(Reason::Tie, explanation.reason);
+    }
+
+    /// Every hand of `rules.hand_size` cards drawn from the rules' deck.
+    fn all_hands(rules: &Rules) -> Vec<Vec<u8>> {
+        let deck_size = u8::try_from(rules.deck.len()).unwrap();
+        (0..rules.hand_size).fold(vec![vec![]], |hands, _| {
+            hands
+                .into_iter()
+                .flat_map(|hand| {
+                    (0..deck_size).map(move |rank| {
+                        let mut hand = hand.clone();
+                        hand.push(rank);
+                        hand
+                    })
+                })
+                .collect()
+        })
+    }
+
+    #[test]
+    fn test_upgrade_matches_exhaustive() {
+        let variants = [
+            ("23J4", 5, vec!['J']),
+            ("23J4", 6, vec!['J']),
+            ("AB12", 7, vec!['1', '2']),
+            ("AB12", 5, vec!['A', 'B', '1', '2']),
+            ("ABC*", 6, vec!['*']),
+        ];
+
+        for (deck, hand_size, wildcards) in variants {
+            let rules = Rules {
+                deck: deck.chars().collect(),
+                hand_size,
+                wildcards,
+                tie_break: TieBreak::CardByCard,
+            };
+            for cards in all_hands(&rules) {
+                assert_eq!(
+                    rules.best_type_exhaustive(&cards),
+                    rules.hand_type(&cards),
+                    "{cards:?}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_exhaustive_part_2() {
+        let rules = Rules::part2();
+        for line in include_str!("../input.txt").lines() {
+            let hand = rules.parse_hand(line);
+            assert_eq!(rules.best_type_exhaustive(&hand.cards), hand.hand_type);
+        }
+    }
+}
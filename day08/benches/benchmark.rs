@@ -0,0 +1,46 @@
+use criterion::{criterion_group, criterion_main, Criterion};
+use day8::{parse_dirs, solution1, solution2, Network};
+
+/// Pseudo-random numbers for generating networks, from a linear congruential generator.
+struct Lcg(u64);
+
+impl Lcg {
+    fn below(&mut self, n: usize) -> usize {
+        self.0 = self
+            .0
+            .wrapping_mul(6_364_136_223_846_793_005)
+            .wrapping_add(1_442_695_040_888_963_407);
+        ((self.0 >> 33) % n as u64) as usize
+    }
+}
+
+/// A network of `n` nodes with pseudo-random neighbours and names of varying length.
+fn generate_network(rng: &mut Lcg, n: usize) -> String {
+    let mut text = String::from("LRRLRLLRLLRRRL\n\n");
+    for i in 0..n {
+        let (left, right) = (rng.below(n), rng.below(n));
+        text.push_str(&format!("N{i:x} = (N{left:x}, N{right:x})\n"));
+    }
+    text
+}
+
+pub fn criterion_benchmark(c: &mut Criterion) {
+    let text = include_str!("../input.txt");
+    c.bench_function("part 1", |b| b.iter(|| solution1(text.lines()).unwrap()));
+    c.bench_function("part 2", |b| b.iter(|| solution2(text.lines())));
+
+    let mut group = c.benchmark_group("large network");
+    group.sample_size(10);
+    let text = generate_network(&mut Lcg(8), 2_000_000);
+    group.bench_function("parse", |b| b.iter(|| Network::parse(text.lines().skip(2))));
+
+    let dirs = parse_dirs(text.lines().next().unwrap());
+    let network = Network::parse(text.lines().skip(2));
+    group.bench_function("walk 10M steps", |b| {
+        b.iter(|| network.walk(0, &dirs).nth(10_000_000))
+    });
+    group.finish();
+}
+
+criterion_group!(benches, criterion_benchmark);
+criterion_main!(benches);
@@ -0,0 +1,643 @@
+use std::collections::{BTreeSet, HashMap, VecDeque};
+use std::fmt::{self, Write};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Direction {
+    Left,
+    Right,
+}
+
+pub fn parse_dirs(line: &str) -> Vec<Direction> {
+    line.as_bytes()
+        .iter()
+        .map(|&c| {
+            if c == b'L' {
+                Direction::Left
+            } else {
+                Direction::Right
+            }
+        })
+        .collect()
+}
+
+/// Returns the names in a line like `AAA = (BBB, CCC)`.
+fn parse_line(line: &str) -> (&str, &str, &str) {
+    let (name, children) = line.split_once(" = ").unwrap();
+    let (left, right) = children
+        .trim_start_matches('(')
+        .trim_end_matches(')')
+        .split_once(", ")
+        .unwrap();
+    (name, left, right)
+}
+
+/// Nodes are interned into ids in the order they're first mentioned, and walked through a dense
+/// table of their left and right neighbours.
+#[derive(Debug, Default)]
+pub struct Network {
+    names: Vec<String>,
+    ids: HashMap<String, u32>,
+    edges: Vec<[u32; 2]>,
+}
+
+impl Network {
+    pub fn parse<'a>(lines: impl IntoIterator<Item = &'a str>) -> Self {
+        let mut network = Self::default();
+        let mut defined: Vec<bool> = vec![];
+
+        for line in lines {
+            let (name, left, right) = parse_line(line);
+            let [id, left, right] = [name, left, right].map(|name| network.intern(name));
+            network.edges[id as usize] = [left, right];
+            defined.resize(network.len(), false);
+            defined[id as usize] = true;
+        }
+
+        if let Some(id) = defined.iter().position(|&d| !d) {
+            panic!("node {} is never defined", network.names[id]);
+        }
+        network
+    }
+
+    fn intern(&mut self, name: &str) -> u32 {
+        if let Some(&id) = self.ids.get(name) {
+            return id;
+        }
+        let id = u32::try_from(self.names.len()).unwrap();
+        self.names.push(name.to_string());
+        self.ids.insert(name.to_string(), id);
+        self.edges.push([id, id]);
+        id
+    }
+
+    pub fn len(&self) -> usize {
+        self.names.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.names.is_empty()
+    }
+
+    pub fn id(&self, name: &str) -> Option<u32> {
+        self.ids.get(name).copied()
+    }
+
+    pub fn name(&self, id: u32) -> &str {
+        &self.names[id as usize]
+    }
+
+    pub fn ids(&self) -> impl Iterator<Item = u32> {
+        0..u32::try_from(self.len()).unwrap()
+    }
+
+    /// Ids of the nodes whose names match `pred`.
+    pub fn find(&self, pred: impl Fn(&str) -> bool) -> Vec<u32> {
+        self.ids().filter(|&id| pred(self.name(id))).collect()
+    }
+
+    /// Whether each node's name matches `pred`, indexed by id.
+    pub fn mask(&self, pred: impl Fn(&str) -> bool) -> Vec<bool> {
+        self.ids().map(|id| pred(self.name(id))).collect()
+    }
+
+    pub fn step(&self, id: u32, dir: Direction) -> u32 {
+        self.edges[id as usize][dir as usize]
+    }
+
+    /// Ids of nodes which can't be reached from any of `starts` by any path.
+    pub fn unreachable_from(&self, starts: impl IntoIterator<Item = u32>) -> Vec<u32> {
+        let mut reached = vec![false; self.len()];
+        let mut queue: VecDeque<u32> = starts.into_iter().collect();
+        while let Some(id) = queue.pop_front() {
+            if reached[id as usize] {
+                continue;
+            }
+            reached[id as usize] = true;
+            queue.extend(self.edges[id as usize]);
+        }
+
+        self.ids().filter(|&id| !reached[id as usize]).collect()
+    }
+
+    /// The network in Graphviz's DOT format, with edges labelled `L` and `R`.
+    pub fn to_dot(&self) -> String {
+        let mut dot = String::from("digraph network {\n");
+        for id in self.ids() {
+            for (dir, label) in [(Direction::Left, 'L'), (Direction::Right, 'R')] {
+                let next = self.step(id, dir);
+                writeln!(
+                    dot,
+                    "    \"{}\" -> \"{}\" [label=\"{label}\"];",
+                    self.name(id),
+                    self.name(next)
+                )
+                .unwrap();
+            }
+        }
+        dot.push_str("}\n");
+        dot
+    }
+
+    /// Every node visited by following `dirs` forever, starting with `start` itself.
+    pub fn walk<'a>(&'a self, start: u32, dirs: &'a [Direction]) -> impl Iterator<Item = u32> + 'a {
+        dirs.iter().cycle().scan(start, move |id, &dir| {
+            let curr = *id;
+            *id = self.step(curr, dir);
+            Some(curr)
+        })
+    }
+}
+
+pub fn parse_input<'a>(lines: impl IntoIterator<Item = &'a str>) -> (Vec<Direction>, Network) {
+    let mut lines = lines.into_iter();
+    let dirs = parse_dirs(lines.next().unwrap());
+    let network = Network::parse(lines.skip(1));
+    (dirs, network)
+}
+
+/// Ids of the nodes ghosts start on.
+pub fn ghost_starts(network: &Network) -> Vec<u32> {
+    network.find(|name| name.ends_with('A'))
+}
+
+/// Whether each node id is one ghosts are looking for.
+pub fn ghost_goals(network: &Network) -> Vec<bool> {
+    network.mask(|name| name.ends_with('Z'))
+}
+
+pub fn solution1<'a>(lines: impl IntoIterator<Item = &'a str>) -> Option<usize> {
+    let (dirs, network) = parse_input(lines);
+
+    // A single walker can stop as soon as it gets there, without mapping out its whole cycle.
+    // After one step per (node, direction) pair it's going round in circles, so ZZZ is out of reach.
+    let start = network.id("AAA")?;
+    let goal = network.id("ZZZ")?;
+    let mut walk = network.walk(start, &dirs).take(network.len() * dirs.len());
+    walk.position(|id| id == goal)
+}
+
+#[derive(Debug)]
+pub struct Cycle {
+    /// Initial pattern of Zs before the cycle starts looping
+    head_zees: Vec<bool>,
+    /// Length of this cycle's loop
+    period: usize,
+    /// Positions of Zs inside the cycle's loop
+    periodic_zees: BTreeSet<usize>,
+}
+
+impl Cycle {
+    /// Number of steps before the cycle starts looping.
+    pub fn head_len(&self) -> usize {
+        self.head_zees.len()
+    }
+
+    pub fn period(&self) -> usize {
+        self.period
+    }
+
+    /// Steps before the loop on which the walker is on a Z.
+    pub fn head_zees(&self) -> Vec<usize> {
+        (0..self.head_len())
+            .filter(|&i| self.head_zees[i])
+            .collect()
+    }
+
+    /// Positions within the loop, counted from its start, on which the walker is on a Z.
+    pub fn periodic_zees(&self) -> Vec<usize> {
+        self.periodic_zees.iter().copied().collect()
+    }
+
+    /// The earliest step on which the walker is on the same node as on `step`.
+    fn reduce_step(&self, step: usize) -> usize {
+        if step < self.head_len() {
+            step
+        } else {
+            self.head_len() + (step - self.head_len()) % self.period
+        }
+    }
+}
+
+/// `is_z` says whether each node id counts as a Z.
+pub fn build_cycle(start: u32, network: &Network, dirs: &[Direction], is_z: &[bool]) -> Cycle {
+    let mut id = start;
+    let mut zees: Vec<bool> = Vec::new();
+
+    // The step at which each node was seen at the start of a pass through the directions.
+    let mut seen: Vec<Option<usize>> = vec![None; network.len()];
+
+    while seen[id as usize].is_none() {
+        seen[id as usize] = Some(zees.len());
+        for &dir in dirs {
+            zees.push(is_z[id as usize]);
+            id = network.step(id, dir);
+        }
+    }
+
+    let split_idx = seen[id as usize].unwrap();
+    let period = zees.len() - split_idx;
+    let periodic_zees_vec = zees.split_off(split_idx);
+    debug_assert_eq!(period, periodic_zees_vec.len());
+
+    let periodic_zees: BTreeSet<usize> = periodic_zees_vec
+        .into_iter()
+        .enumerate()
+        .filter_map(|(i, b)| if b { Some(i) } else { None })
+        .collect();
+
+    Cycle {
+        period,
+        periodic_zees,
+        head_zees: zees,
+    }
+}
+
+/// Step counts at which a walker is on a Z: some which happen once before its path starts
+/// looping, and arithmetic progressions for the Zs inside the loop.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Hits {
+    pub once: BTreeSet<u128>,
+    /// `(first, period)` for the steps `first`, `first + period`, `first + 2 * period`, ...
+    pub progressions: Vec<(u128, u128)>,
+}
+
+impl Hits {
+    pub fn contains(&self, step: u128) -> bool {
+        self.once.contains(&step)
+            || self
+                .progressions
+                .iter()
+                .any(|&(first, period)| step >= first && (step - first).is_multiple_of(period))
+    }
+
+    /// Steps at which both walkers are on a Z.
+    pub fn intersect(&self, other: &Hits) -> Result<Hits, StepOverflow> {
+        let once = self
+            .once
+            .iter()
+            .filter(|&&step| other.contains(step))
+            .chain(other.once.iter().filter(|&&step| self.contains(step)))
+            .copied()
+            .collect();
+
+        let mut progressions: Vec<(u128, u128)> = vec![];
+        for &p1 in &self.progressions {
+            for &p2 in &other.progressions {
+                progressions.extend(merge_progressions(p1, p2)?);
+            }
+        }
+        progressions.sort_unstable();
+        progressions.dedup();
+
+        Ok(Hits { once, progressions })
+    }
+
+    /// The first step in the set, or `None` if it's empty and the walkers never line up.
+    pub fn first(&self) -> Option<u128> {
+        let once = self.once.first().copied();
+        let periodic = self.progressions.iter().map(|&(first, _)| first).min();
+        once.into_iter().chain(periodic).min()
+    }
+}
+
+impl Cycle {
+    pub fn hits(&self) -> Hits {
+        let head_len = self.head_zees.len() as u128;
+        let period = self.period as u128;
+
+        Hits {
+            once: (0..)
+                .zip(&self.head_zees)
+                .filter_map(|(i, &z)| z.then_some(i))
+                .collect(),
+            progressions: self
+                .periodic_zees
+                .iter()
+                .map(|&z| (head_len + z as u128, period))
+                .collect(),
+        }
+    }
+}
+
+/// Returns `(g, x, y)` with `a * x + b * y = g = gcd(a, b)`.
+fn extended_gcd(a: i128, b: i128) -> (i128, i128, i128) {
+    if b == 0 {
+        (a, 1, 0)
+    } else {
+        let (g, x, y) = extended_gcd(b, a % b);
+        (g, y, x - (a / b) * y)
+    }
+}
+
+/// `a * b % m`, without overflowing when `a` and `b` are already below `m`.
+fn mul_mod(a: u128, b: u128, m: u128) -> u128 {
+    if let Some(product) = a.checked_mul(b) {
+        return product % m;
+    }
+    // Double and add, which can't overflow because everything stays below m <= 2^127.
+    let (mut a, mut b, mut out) = (a, b, 0);
+    while b > 0 {
+        if b & 1 == 1 {
+            out = (out + a) % m;
+        }
+        a = (a + a) % m;
+        b >>= 1;
+    }
+    out
+}
+
+/// Intersects two arithmetic progressions using the generalised Chinese remainder theorem.
+/// `Ok(None)` if they never meet, and an error if the combined period or first step doesn't fit
+/// in a `u128`.
+fn merge_progressions(
+    (a, m): (u128, u128),
+    (b, n): (u128, u128),
+) -> Result<Option<(u128, u128)>, StepOverflow> {
+    let signed = |x: u128| i128::try_from(x).map_err(|_| StepOverflow);
+    let (g, x, _) = extended_gcd(signed(m)?, signed(n)?);
+    let diff = signed(b)? - signed(a)?;
+    if diff % g != 0 {
+        return Ok(None);
+    }
+
+    // Solve a + m * t = b (mod n) for t.
+    let n_g = n / g as u128;
+    let t = mul_mod(
+        (diff / g).rem_euclid(signed(n_g)?) as u128,
+        x.rem_euclid(signed(n_g)?) as u128,
+        n_g,
+    );
+    let period = (m / g as u128).checked_mul(n).ok_or(StepOverflow)?;
+    // t < n / g, so m * t < period.
+    let mut first = a.checked_add(m * t).ok_or(StepOverflow)? % period;
+
+    // Both progressions have to have started.
+    let start = a.max(b);
+    if first < start {
+        first = (start - first)
+            .div_ceil(period)
+            .checked_mul(period)
+            .and_then(|skipped| first.checked_add(skipped))
+            .ok_or(StepOverflow)?;
+    }
+    Ok(Some((first, period)))
+}
+
+/// The first step on which the walkers line up is too far away to count.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StepOverflow;
+
+impl fmt::Display for StepOverflow {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "the walkers line up too late to count the steps")
+    }
+}
+
+impl std::error::Error for StepOverflow {}
+
+/// Where a group of walkers lined up.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Alignment {
+    pub steps: usize,
+    /// The node each walker was on, in the order their starts appear in the network.
+    pub nodes: Vec<u32>,
+}
+
+/// Walks from every node whose name matches `is_start` at once until all walkers are on a node
+/// whose name matches `is_goal` on the same step. Returns `None` if that never happens, and an
+/// error if it happens too late to count in a `usize`.
+pub fn walk_together(
+    network: &Network,
+    dirs: &[Direction],
+    is_start: impl Fn(&str) -> bool,
+    is_goal: impl Fn(&str) -> bool,
+) -> Result<Option<Alignment>, StepOverflow> {
+    let starts = network.find(is_start);
+    let goals = network.mask(is_goal);
+    let cycles: Vec<Cycle> = starts
+        .iter()
+        .map(|&start| build_cycle(start, network, dirs, &goals))
+        .collect();
+
+    let mut hits = cycles.iter().map(Cycle::hits);
+    let Some(first) = hits.next() else {
+        return Ok(None);
+    };
+    let hits = hits.try_fold(first, |h1, h2| h1.intersect(&h2))?;
+    let Some(steps) = hits.first() else {
+        return Ok(None);
+    };
+    let steps = usize::try_from(steps).map_err(|_| StepOverflow)?;
+
+    // Each walker only needs to go as far as the first time it's where it'll be at `steps`.
+    let nodes = starts
+        .iter()
+        .zip(&cycles)
+        .map(|(&start, cycle)| {
+            let mut walk = network.walk(start, dirs);
+            walk.nth(cycle.reduce_step(steps)).unwrap()
+        })
+        .collect();
+
+    Ok(Some(Alignment { steps, nodes }))
+}
+
+/// The first step on which every ghost is on a Z at the same time, or `None` if that never
+/// happens.
+pub fn first_common_step<'a>(
+    lines: impl IntoIterator<Item = &'a str>,
+) -> Result<Option<usize>, StepOverflow> {
+    let (dirs, network) = parse_input(lines);
+    let alignment = walk_together(
+        &network,
+        &dirs,
+        |name| name.ends_with('A'),
+        |name| name.ends_with('Z'),
+    )?;
+    Ok(alignment.map(|alignment| alignment.steps))
+}
+
+pub fn solution2<'a>(lines: impl IntoIterator<Item = &'a str>) -> usize {
+    first_common_step(lines).unwrap().unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn part_1() {
+        let input = include_str!("../example.txt").lines();
+        assert_eq!(Some(6), solution1(input));
+    }
+
+    #[test]
+    fn test_unreachable_zzz() {
+        let input = "L\n\nAAA = (AAA, AAA)\nZZZ = (ZZZ, ZZZ)".lines();
+        assert_eq!(None, solution1(input));
+    }
+
+    #[test]
+    fn part_2() {
+        let input = include_str!("../example2.txt").lines();
+        assert_eq!(6, solution2(input));
+    }
+
+    #[test]
+    fn test_never() {
+        // 11A reaches a Z on every odd step and 22A on every even one.
+        let input = "L\n\n11A = (11Z, 11Z)\n11Z = (11A, 11A)\n22A = (22B, 22B)\n22B = (22Z, 22Z)\n22Z = (22B, 22B)";
+        assert_eq!(Ok(None), first_common_step(input.lines()));
+
+        let input = "L\n\n11A = (11B, 11B)\n11B = (11B, 11B)\n22A = (22Z, 22Z)\n22Z = (22A, 22A)";
+        assert_eq!(Ok(None), first_common_step(input.lines()));
+    }
+
+    #[test]
+    fn test_merge_progressions() {
+        assert_eq!(Ok(Some((9, 12))), merge_progressions((1, 4), (3, 6)));
+        assert_eq!(Ok(None), merge_progressions((0, 4), (1, 6)));
+        assert_eq!(Ok(Some((30, 6))), merge_progressions((30, 2), (0, 3)));
+
+        for (p1, p2) in [((3, 4), (5, 6)), ((10, 7), (2, 5)), ((0, 1), (7, 9))] {
+            let brute = (0..1000).find(|&step| {
+                [p1, p2]
+                    .iter()
+                    .all(|&(first, period)| step >= first && (step - first) % period == 0)
+            });
+            let merged = merge_progressions(p1, p2).unwrap();
+            assert_eq!(brute, merged.map(|(first, _)| first));
+        }
+    }
+
+    #[test]
+    fn test_merge_large_progressions() {
+        // Both periods fit in a u64, but solving for the first step multiplies two numbers
+        // almost as big as them, which doesn't fit in an i128.
+        let (m, n) = (u128::from(u64::MAX), u128::from(u64::MAX - 1));
+        let (first, period) = merge_progressions((3, m), (5, n)).unwrap().unwrap();
+        assert_eq!(m * n, period);
+        assert!(first < period);
+        assert_eq!(0, (first - 3) % m);
+        assert_eq!(0, (first - 5) % n);
+
+        // Coprime periods whose product doesn't fit.
+        let (m, n) = (1 << 100, (1 << 100) - 1);
+        assert_eq!(Err(StepOverflow), merge_progressions((0, m), (1, n)));
+        assert_eq!(Err(StepOverflow), merge_progressions((0, 1 << 127), (1, 3)));
+
+        let hits = |first, period| Hits {
+            once: BTreeSet::new(),
+            progressions: vec![(first, period)],
+        };
+        let combined = hits(0, 1 << 60).intersect(&hits(1, (1 << 60) - 1)).unwrap();
+        assert_eq!(
+            Err(StepOverflow),
+            combined.intersect(&hits(2, (1 << 60) + 1))
+        );
+    }
+
+    #[test]
+    fn test_input() {
+        let input = include_str!("../input.txt").lines();
+        assert_eq!(Ok(Some(9_177_460_370_549)), first_common_step(input));
+    }
+
+    #[test]
+    fn test_long_names() {
+        let input = "RLL\n\nstart = (middle, start)\nmiddle = (Z, start)\nZ = (Z, Z)";
+        let mut lines = input.lines();
+        let dirs = parse_dirs(lines.next().unwrap());
+        let network = Network::parse(lines.skip(1));
+
+        assert_eq!(3, network.len());
+        let start = network.id("start").unwrap();
+        let names: Vec<&str> = network
+            .walk(start, &dirs)
+            .take(4)
+            .map(|id| network.name(id))
+            .collect();
+        assert_eq!(vec!["start", "start", "middle", "Z"], names);
+    }
+
+    #[test]
+    fn test_analysis() {
+        let (dirs, network) = parse_input(include_str!("../example2.txt").lines());
+        let is_z = ghost_goals(&network);
+        let starts = ghost_starts(&network);
+
+        let cycle = build_cycle(starts[1], &network, &dirs, &is_z);
+        assert_eq!("22A", network.name(starts[1]));
+        assert_eq!((2, 6), (cycle.head_len(), cycle.period()));
+        assert_eq!(Vec::<usize>::new(), cycle.head_zees());
+        assert_eq!(vec![1, 4], cycle.periodic_zees());
+
+        assert!(network.unreachable_from(starts).is_empty());
+        let xxx = network.id("XXX").unwrap();
+        let unreachable: Vec<&str> = network
+            .unreachable_from([xxx])
+            .into_iter()
+            .map(|id| network.name(id))
+            .collect();
+        assert_eq!(
+            vec!["11A", "11B", "11Z", "22A", "22B", "22C", "22Z"],
+            unreachable
+        );
+
+        let dot = network.to_dot();
+        assert!(dot.starts_with("digraph network {\n    \"11A\" -> \"11B\" [label=\"L\"];\n"));
+        assert_eq!(2 * network.len() + 2, dot.lines().count());
+    }
+
+    #[test]
+    fn test_walk_together() {
+        let (dirs, network) = parse_input(include_str!("../example2.txt").lines());
+        let names = |nodes: &[u32]| -> Vec<String> {
+            nodes
+                .iter()
+                .map(|&id| network.name(id).to_string())
+                .collect()
+        };
+
+        let is_start = |name: &str| name.ends_with('A');
+        let alignment = walk_together(&network, &dirs, is_start, |name| name.ends_with('Z'))
+            .unwrap()
+            .unwrap();
+        assert_eq!(6, alignment.steps);
+        assert_eq!(vec!["11Z", "22Z"], names(&alignment.nodes));
+
+        // Both ghosts are on a B after one step, and again after four.
+        let alignment = walk_together(&network, &dirs, is_start, |name| name.ends_with('B'))
+            .unwrap()
+            .unwrap();
+        assert_eq!(1, alignment.steps);
+        let b_after_first = |name: &str| name.ends_with('B') || name == "22C";
+        let alignment = walk_together(&network, &dirs, |name| name == "11A", b_after_first)
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            (1, vec!["11B".to_string()]),
+            (alignment.steps, names(&alignment.nodes))
+        );
+
+        assert_eq!(
+            Ok(None),
+            walk_together(
+                &network,
+                &dirs,
+                |name| name == "XXX",
+                |name| name.ends_with('Z')
+            )
+        );
+
+        // The alignment on the real input is far too late to walk to directly.
+        let (dirs, network) = parse_input(include_str!("../input.txt").lines());
+        let alignment = walk_together(&network, &dirs, is_start, |name| name.ends_with('Z'))
+            .unwrap()
+            .unwrap();
+        assert!(alignment
+            .nodes
+            .iter()
+            .all(|&id| network.name(id).ends_with('Z')));
+    }
+}
@@ -1,5 +1,19 @@
 use criterion::{criterion_group, criterion_main, Criterion};
-use day9::{solution1, solution1_fd};
+use day9::{solution1, solution1_fd, solution1_fd_with, Arithmetic};
+
+/// Lines of low degree polynomials, long enough for the Pascal's triangle weights to overflow.
+/// The weights wrap silently in release builds, so only that path's speed means anything.
+fn long_lines(lines: i64, len: i64) -> String {
+    (0..lines)
+        .map(|line| {
+            (0..len)
+                .map(|x| (x * x * (x - line) - 3 * line).to_string())
+                .collect::<Vec<_>>()
+                .join(" ")
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
 
 pub fn criterion_benchmark(c: &mut Criterion) {
     let text = include_str!("../input.txt");
@@ -10,6 +24,21 @@ pub fn criterion_benchmark(c: &mut Criterion) {
     group.bench_function("First differences", |b| b.iter(|| {
         solution1_fd(text.lines())
     }));
+    group.finish();
+
+    let long = long_lines(50, 2000);
+    let mut group = c.benchmark_group("Long lines");
+    group.bench_function("Pascal's Triangle", |b| b.iter(|| solution1(long.lines())));
+    group.bench_function("First differences, checked", |b| {
+        b.iter(|| solution1_fd_with::<i64>(long.lines(), Arithmetic::Checked))
+    });
+    group.bench_function("First differences, wrapping", |b| {
+        b.iter(|| solution1_fd_with::<i64>(long.lines(), Arithmetic::Wrapping))
+    });
+    group.bench_function("First differences, i128", |b| {
+        b.iter(|| solution1_fd_with::<i128>(long.lines(), Arithmetic::Checked))
+    });
+    group.finish();
 }
 
 criterion_group!(benches, criterion_benchmark);
@@ -1,3 +1,6 @@
+use std::fmt;
+use std::str::FromStr;
+
 #[must_use]
 pub fn gen_coeffs(n: usize) -> Vec<i64> {
     let signed_n: i64 = n.try_into().unwrap();
@@ -43,26 +46,108 @@ pub fn solution1<'a>(lines: impl IntoIterator<Item = &'a str>) -> i64 {
         .sum()
 }
 
-fn extrapolate_history(history: &[i32]) -> i32 {
-    let diffs: Vec<i32> = history.windows(2).map(|win| win[1] - win[0]).collect();
-    if diffs.iter().all(|&d| d == 0) {
-        history[0]
-    } else {
-        history[history.len() - 1] + extrapolate_history(&diffs)
+/// Signed integers the difference table can be built from.
+pub trait Int: Copy + Eq + FromStr {
+    const ZERO: Self;
+    fn checked_add(self, other: Self) -> Option<Self>;
+    fn checked_sub(self, other: Self) -> Option<Self>;
+    fn wrapping_add(self, other: Self) -> Self;
+    fn wrapping_sub(self, other: Self) -> Self;
+}
+
+macro_rules! impl_int {
+    ($($t:ty),*) => {
+        $(
+            impl Int for $t {
+                const ZERO: Self = 0;
+                fn checked_add(self, other: Self) -> Option<Self> {
+                    <$t>::checked_add(self, other)
+                }
+                fn checked_sub(self, other: Self) -> Option<Self> {
+                    <$t>::checked_sub(self, other)
+                }
+                fn wrapping_add(self, other: Self) -> Self {
+                    <$t>::wrapping_add(self, other)
+                }
+                fn wrapping_sub(self, other: Self) -> Self {
+                    <$t>::wrapping_sub(self, other)
+                }
+            }
+        )*
+    };
+}
+
+impl_int!(i8, i16, i32, i64, i128, isize);
+
+/// What to do when a difference or sum doesn't fit.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Arithmetic {
+    /// Give up on the whole extrapolation.
+    Checked,
+    /// Wrap around, which still gives the right answer whenever it fits.
+    Wrapping,
+}
+
+impl Arithmetic {
+    fn add<T: Int>(self, a: T, b: T) -> Option<T> {
+        match self {
+            Self::Checked => a.checked_add(b),
+            Self::Wrapping => Some(a.wrapping_add(b)),
+        }
+    }
+
+    fn sub<T: Int>(self, a: T, b: T) -> Option<T> {
+        match self {
+            Self::Checked => a.checked_sub(b),
+            Self::Wrapping => Some(a.wrapping_sub(b)),
+        }
     }
 }
 
-pub fn solution1_fd<'a>(lines: impl IntoIterator<Item = &'a str>) -> i32 {
-    lines
-        .into_iter()
-        .map(|line| {
-            let history: Vec<i32> = line
-                .split_ascii_whitespace()
-                .map(|s| s.parse::<i32>().unwrap())
-                .collect();
-            extrapolate_history(&history)
-        })
-    .sum()
+/// The value after the end of `history`, found by building the difference table in place.
+/// `None` only if checked arithmetic overflows. An empty history extrapolates to zero.
+pub fn extrapolate_next<T: Int>(history: &[T], arithmetic: Arithmetic) -> Option<T> {
+    let mut row = history.to_vec();
+    let mut next = T::ZERO;
+
+    // Each pass replaces the front of `row` with its differences, leaving the last value of
+    // the previous row just past them.
+    for len in (1..=row.len()).rev() {
+        next = arithmetic.add(next, row[len - 1])?;
+        let mut all_zero = true;
+        for i in 0..len - 1 {
+            row[i] = arithmetic.sub(row[i + 1], row[i])?;
+            all_zero &= row[i] == T::ZERO;
+        }
+        if all_zero {
+            break;
+        }
+    }
+
+    Some(next)
+}
+
+/// Part 1 by finite differences in any integer width. `None` if checked arithmetic overflows.
+pub fn solution1_fd_with<'a, T>(
+    lines: impl IntoIterator<Item = &'a str>,
+    arithmetic: Arithmetic,
+) -> Option<T>
+where
+    T: Int,
+    T::Err: fmt::Debug,
+{
+    lines.into_iter().try_fold(T::ZERO, |total, line| {
+        let history: Vec<T> = line
+            .split_ascii_whitespace()
+            .map(|s| s.parse::<T>().unwrap())
+            .collect();
+        let next = extrapolate_next(&history, arithmetic)?;
+        arithmetic.add(total, next)
+    })
+}
+
+pub fn solution1_fd<'a>(lines: impl IntoIterator<Item = &'a str>) -> i64 {
+    solution1_fd_with(lines, Arithmetic::Checked).unwrap()
 }
 
 /// Literally just part 1 but I reverse the order of numbers in each line
@@ -84,10 +169,304 @@ pub fn solution2<'a>(lines: impl IntoIterator<Item = &'a str>) -> i64 {
         .sum()
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExtrapolationError {
+    /// There's no polynomial through an empty history.
+    Empty,
+    /// A difference, binomial or the result itself doesn't fit in an `i128`.
+    Overflow,
+}
+
+impl fmt::Display for ExtrapolationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Empty => write!(f, "can't extrapolate an empty history"),
+            Self::Overflow => write!(f, "extrapolation overflowed an i128"),
+        }
+    }
+}
+
+impl std::error::Error for ExtrapolationError {}
+
+fn differences(row: &[i128]) -> Result<Vec<i128>, ExtrapolationError> {
+    row.windows(2)
+        .map(|win| win[1].checked_sub(win[0]))
+        .collect::<Option<_>>()
+        .ok_or(ExtrapolationError::Overflow)
+}
+
+/// Value at `x` of the lowest degree polynomial with `history[i]` as its value at `i`.
+///
+/// This is Newton's forward difference form, the sum of `C(x, k) * Δᵏ history[0]`. The
+/// generalised binomial `C(x, k)` is an integer for any integer `x`, so every step is exact.
+pub fn evaluate(history: &[i64], x: i64) -> Result<i128, ExtrapolationError> {
+    use ExtrapolationError::Overflow;
+
+    if history.is_empty() {
+        return Err(ExtrapolationError::Empty);
+    }
+    if let Some(&value) = usize::try_from(x).ok().and_then(|x| history.get(x)) {
+        return Ok(value.into());
+    }
+
+    let x = i128::from(x);
+    let mut row: Vec<i128> = history.iter().map(|&h| h.into()).collect();
+    let mut binomial: i128 = 1;
+    let mut total: i128 = 0;
+    let mut k: i128 = 0;
+
+    // Once a row is all zeros, so is every row after it.
+    while row.iter().any(|&d| d != 0) {
+        if k > 0 {
+            // C(x, k) = C(x, k - 1) * (x - k + 1) / k, and the division is exact.
+            binomial = binomial.checked_mul(x - k + 1).ok_or(Overflow)? / k;
+            if binomial == 0 {
+                // x is one of 0..k, so every later binomial is zero too.
+                break;
+            }
+        }
+        total = binomial
+            .checked_mul(row[0])
+            .and_then(|term| total.checked_add(term))
+            .ok_or(Overflow)?;
+        row = differences(&row)?;
+        k += 1;
+    }
+
+    Ok(total)
+}
+
+/// The value `steps` places after the end of `history`.
+pub fn extrapolate_forward(history: &[i64], steps: i64) -> Result<i128, ExtrapolationError> {
+    let last = i64::try_from(history.len()).map_err(|_| ExtrapolationError::Overflow)? - 1;
+    let x = last
+        .checked_add(steps)
+        .ok_or(ExtrapolationError::Overflow)?;
+    evaluate(history, x)
+}
+
+/// The value `steps` places before the start of `history`.
+pub fn extrapolate_backward(history: &[i64], steps: i64) -> Result<i128, ExtrapolationError> {
+    let x = steps.checked_neg().ok_or(ExtrapolationError::Overflow)?;
+    evaluate(history, x)
+}
+
+/// A polynomial with rational coefficients, kept as integers over a common denominator.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Polynomial {
+    /// Constant term first.
+    pub numerators: Vec<i128>,
+    /// Always positive, and shares no factor with all of the numerators.
+    pub denominator: i128,
+}
+
+impl Polynomial {
+    /// Builds the polynomial from its Newton forward difference coefficients, the sum of
+    /// `newton[k] * C(x, k)`. `None` if it doesn't fit in `i128`s.
+    fn from_newton(newton: &[i128]) -> Option<Self> {
+        let degree = newton.len().saturating_sub(1);
+        let factorial = |n: usize| (1..=n as i128).try_fold(1i128, |acc, i| acc.checked_mul(i));
+        let denominator = factorial(degree)?;
+
+        // Scaled by degree!, each C(x, k) becomes degree! / k! * x(x - 1)...(x - k + 1).
+        let mut numerators = vec![0; degree + 1];
+        let mut falling: Vec<i128> = vec![1];
+        for (k, &coeff) in newton.iter().enumerate() {
+            let scale = coeff.checked_mul(denominator / factorial(k)?)?;
+            for (numerator, &f) in numerators.iter_mut().zip(&falling) {
+                *numerator = f.checked_mul(scale)?.checked_add(*numerator)?;
+            }
+
+            // Multiply by (x - k) ready for the next term.
+            let k = k as i128;
+            let mut next: Vec<i128> = vec![0; falling.len() + 1];
+            for (i, &f) in falling.iter().enumerate() {
+                next[i + 1] = next[i + 1].checked_add(f)?;
+                next[i] = f.checked_mul(k)?.checked_neg()?.checked_add(next[i])?;
+            }
+            falling = next;
+        }
+
+        let divisor = numerators.iter().fold(denominator, |acc, &n| gcd(acc, n));
+        Some(Self {
+            numerators: numerators.iter().map(|n| n / divisor).collect(),
+            denominator: denominator / divisor,
+        })
+    }
+
+    pub fn degree(&self) -> usize {
+        self.numerators.len() - 1
+    }
+
+    /// Value at `x`, which is always an integer for the polynomials built from histories.
+    pub fn eval(&self, x: i64) -> Option<i128> {
+        let x = i128::from(x);
+        let total = self
+            .numerators
+            .iter()
+            .rev()
+            .try_fold(0i128, |acc, &n| acc.checked_mul(x)?.checked_add(n))?;
+        Some(total / self.denominator)
+    }
+}
+
+fn gcd(a: i128, b: i128) -> i128 {
+    if b == 0 {
+        a.abs()
+    } else {
+        gcd(b, a % b)
+    }
+}
+
+impl fmt::Display for Polynomial {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut terms = self
+            .numerators
+            .iter()
+            .enumerate()
+            .rev()
+            .filter(|&(_, &n)| n != 0)
+            .peekable();
+
+        let wrap = self.denominator != 1 && terms.clone().count() > 1;
+        if wrap {
+            write!(f, "(")?;
+        }
+        if terms.peek().is_none() {
+            write!(f, "0")?;
+        }
+        let mut first = true;
+        for (power, &n) in terms {
+            let sign = match (first, n < 0) {
+                (true, true) => "-",
+                (true, false) => "",
+                (false, true) => " - ",
+                (false, false) => " + ",
+            };
+            first = false;
+            write!(f, "{sign}")?;
+            if n.abs() != 1 || power == 0 {
+                write!(f, "{}", n.abs())?;
+            }
+            match power {
+                0 => {}
+                1 => write!(f, "x")?,
+                _ => write!(f, "x^{power}")?,
+            }
+        }
+        if wrap {
+            write!(f, ")")?;
+        }
+        if self.denominator != 1 {
+            write!(f, " / {}", self.denominator)?;
+        }
+        Ok(())
+    }
+}
+
+/// Something about a line which makes its extrapolation unreliable.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Issue {
+    /// No row of the difference table is all zeros, so any extrapolation is a guess.
+    NeverZero,
+    /// The line doesn't have as many values as the first, which `solution1` and `solution2`
+    /// assume every line does.
+    Length { expected: usize, found: usize },
+    /// The difference table or the polynomial's coefficients don't fit in `i128`s.
+    Overflow,
+}
+
+impl fmt::Display for Issue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NeverZero => write!(f, "differences never reach all zeros"),
+            Self::Length { expected, found } => {
+                write!(f, "has {found} values rather than {expected}")
+            }
+            Self::Overflow => write!(f, "overflowed an i128"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Diagnostics {
+    /// The history followed by each row of differences, down to the first all-zero row if
+    /// there is one.
+    pub table: Vec<Vec<i128>>,
+    /// Degree of the polynomial the history follows, if its differences reach zero.
+    pub degree: Option<usize>,
+    pub polynomial: Option<Polynomial>,
+    pub issues: Vec<Issue>,
+}
+
+pub fn diagnose(history: &[i64]) -> Diagnostics {
+    let mut table: Vec<Vec<i128>> = vec![history.iter().map(|&h| h.into()).collect()];
+    let mut issues = vec![];
+
+    let last = loop {
+        let row = table.last().unwrap();
+        if row.is_empty() {
+            // A single value was left over, or the history was empty.
+            table.pop();
+            issues.push(Issue::NeverZero);
+            break None;
+        }
+        if row.iter().all(|&d| d == 0) {
+            break Some(table.len() - 1);
+        }
+        match differences(row) {
+            Ok(diffs) => table.push(diffs),
+            Err(_) => {
+                issues.push(Issue::Overflow);
+                break None;
+            }
+        }
+    };
+
+    // The zero row comes one after the highest non-zero difference, except for all-zero
+    // histories.
+    let degree = last.map(|last| last.saturating_sub(1));
+    let polynomial = last.and_then(|last| {
+        let newton: Vec<i128> = table[..last.max(1)].iter().map(|row| row[0]).collect();
+        let polynomial = Polynomial::from_newton(&newton);
+        if polynomial.is_none() {
+            issues.push(Issue::Overflow);
+        }
+        polynomial
+    });
+
+    Diagnostics {
+        table,
+        degree,
+        polynomial,
+        issues,
+    }
+}
+
+/// Diagnoses every line, also flagging lines whose length differs from the first's.
+pub fn diagnose_lines<'a>(lines: impl IntoIterator<Item = &'a str>) -> Vec<Diagnostics> {
+    let matrix = parse_matrix(lines);
+    let expected = matrix.first().map_or(0, Vec::len);
+
+    matrix
+        .iter()
+        .map(|history| {
+            let mut diagnostics = diagnose(history);
+            if history.len() != expected {
+                diagnostics.issues.push(Issue::Length {
+                    expected,
+                    found: history.len(),
+                });
+            }
+            diagnostics
+        })
+        .collect()
+}
+
 
 #[cfg(test)]
 mod tests {
-    use crate::{solution1, solution2, solution1_fd};
+    use crate::*;
 
     #[test]
     fn test_example_1() {
@@ -103,6 +482,78 @@ mod tests {
         assert_eq!(1637452029, p1);
     }
 
+    #[test]
+    fn test_fd_widths() {
+        let input = include_str!("../input.txt");
+        assert_eq!(
+            Some(1637452029),
+            solution1_fd_with::<i32>(input.lines(), Arithmetic::Checked)
+        );
+        assert_eq!(
+            Some(1637452029),
+            solution1_fd_with::<i128>(input.lines(), Arithmetic::Wrapping)
+        );
+
+        // The total wraps an i32 when doubled, but each line still fits.
+        let doubled: Vec<String> = input
+            .lines()
+            .map(|line| {
+                line.split_ascii_whitespace()
+                    .map(|s| (2 * s.parse::<i64>().unwrap()).to_string())
+                    .collect::<Vec<_>>()
+                    .join(" ")
+            })
+            .collect();
+        let doubled = doubled.iter().map(String::as_str);
+        assert_eq!(
+            None,
+            solution1_fd_with::<i32>(doubled.clone(), Arithmetic::Checked)
+        );
+        assert_eq!(
+            Some((2 * 1637452029_i64) as i32),
+            solution1_fd_with::<i32>(doubled.clone(), Arithmetic::Wrapping)
+        );
+        assert_eq!(
+            Some(2 * 1637452029),
+            solution1_fd_with::<i64>(doubled, Arithmetic::Checked)
+        );
+    }
+
+    #[test]
+    fn test_extrapolate_next_wrapping() {
+        // Wrapping arithmetic agrees with the exact answer modulo the width.
+        let histories: [&[i128]; 4] = [
+            &[-100, 100, -100, 100],
+            &[127, -128, 127],
+            &[1, 2, 4, 8, 16, 32, 64],
+            &[],
+        ];
+        for history in histories {
+            let exact = extrapolate_next(history, Arithmetic::Checked).unwrap();
+            let narrow: Vec<i8> = history.iter().map(|&h| h as i8).collect();
+            assert_eq!(
+                Some(exact as i8),
+                extrapolate_next(&narrow, Arithmetic::Wrapping),
+                "{history:?}"
+            );
+        }
+        assert_eq!(None, extrapolate_next(&[127_i8, -128], Arithmetic::Checked));
+    }
+
+    #[test]
+    fn test_extrapolate_next_long() {
+        let cubic = |x: i64| x * x * x - 7 * x * x + 3;
+        let history: Vec<i64> = (0..5000).map(cubic).collect();
+        assert_eq!(
+            Some(cubic(5000)),
+            extrapolate_next(&history, Arithmetic::Checked)
+        );
+        assert_eq!(
+            Ok(i128::from(cubic(5000))),
+            extrapolate_forward(&history, 1)
+        );
+    }
+
     #[test]
     fn test_part_1_fd() {
         let input = include_str!("../input.txt");
@@ -116,4 +567,147 @@ mod tests {
         let p2 = solution2(input);
         assert_eq!(2, p2);
     }
+
+    #[test]
+    fn test_extrapolate_input() {
+        let matrix = parse_matrix(include_str!("../input.txt").lines());
+        let forward: i128 = matrix
+            .iter()
+            .map(|history| extrapolate_forward(history, 1).unwrap())
+            .sum();
+        let backward: i128 = matrix
+            .iter()
+            .map(|history| extrapolate_backward(history, 1).unwrap())
+            .sum();
+        assert_eq!((1637452029, 908), (forward, backward));
+    }
+
+    #[test]
+    fn test_evaluate() {
+        let history = [10, 13, 16, 21, 30, 45];
+        for (x, &value) in history.iter().enumerate() {
+            assert_eq!(Ok(i128::from(value)), evaluate(&history, x as i64));
+        }
+        assert_eq!(Ok(68), extrapolate_forward(&history, 1));
+        assert_eq!(Ok(5), extrapolate_backward(&history, 1));
+        assert_eq!(Ok(101), extrapolate_forward(&history, 2));
+        assert_eq!(Ok(-19), extrapolate_backward(&history, 3));
+        assert_eq!(Ok(5), extrapolate_forward(&[5], 1000));
+        assert_eq!(Err(ExtrapolationError::Empty), evaluate(&[], 0));
+    }
+
+    #[test]
+    fn test_long_history() {
+        // Far too long for the i64 binomial weights of `gen_coeffs`.
+        let cubic = |x: i64| i128::from(x).pow(3) - 5 * i128::from(x);
+        let history: Vec<i64> = (0..100).map(|x| cubic(x) as i64).collect();
+        for x in [-1_000_000, -50, 100, 150, 1 << 40] {
+            assert_eq!(Ok(cubic(x)), evaluate(&history, x), "{x}");
+        }
+    }
+
+    #[test]
+    fn test_overflow() {
+        // The differences of an alternating sequence double at every level.
+        let history: Vec<i64> = (0..130).map(|i| if i % 2 == 0 { 1 } else { -1 }).collect();
+        assert_eq!(Err(ExtrapolationError::Overflow), evaluate(&history, 130));
+        assert_eq!(Ok(-1), evaluate(&history, 129));
+        assert_eq!(
+            Err(ExtrapolationError::Overflow),
+            extrapolate_forward(&[1, 2], i64::MAX)
+        );
+        assert_eq!(
+            Err(ExtrapolationError::Overflow),
+            extrapolate_forward(&[0, 1, 4], i64::MAX - 1)
+        );
+    }
+
+    #[test]
+    fn test_diagnose() {
+        let diagnostics = diagnose(&[1, 3, 6, 10, 15, 21]);
+        assert_eq!(
+            vec![
+                vec![1, 3, 6, 10, 15, 21],
+                vec![2, 3, 4, 5, 6],
+                vec![1, 1, 1, 1],
+                vec![0, 0, 0],
+            ],
+            diagnostics.table
+        );
+        assert_eq!(Some(2), diagnostics.degree);
+        let polynomial = diagnostics.polynomial.unwrap();
+        assert_eq!(vec![2, 3, 1], polynomial.numerators);
+        assert_eq!(2, polynomial.denominator);
+        assert_eq!("(x^2 + 3x + 2) / 2", polynomial.to_string());
+        assert!(diagnostics.issues.is_empty());
+
+        let diagnostics = diagnose(&[0, 3, 6, 9, 12, 15]);
+        assert_eq!(Some(1), diagnostics.degree);
+        assert_eq!("3x", diagnostics.polynomial.unwrap().to_string());
+
+        let diagnostics = diagnose(&[7, 7]);
+        assert_eq!(Some(0), diagnostics.degree);
+        assert_eq!("7", diagnostics.polynomial.unwrap().to_string());
+
+        let diagnostics = diagnose(&[0, 0, 0]);
+        assert_eq!(Some(0), diagnostics.degree);
+        assert_eq!("0", diagnostics.polynomial.unwrap().to_string());
+    }
+
+    #[test]
+    fn test_diagnose_never_zero() {
+        let diagnostics = diagnose(&[1, 2, 4, 8]);
+        assert_eq!(None, diagnostics.degree);
+        assert_eq!(None, diagnostics.polynomial);
+        assert_eq!(vec![Issue::NeverZero], diagnostics.issues);
+        assert_eq!(
+            vec![vec![1, 2, 4, 8], vec![1, 2, 4], vec![1, 2], vec![1]],
+            diagnostics.table
+        );
+
+        assert_eq!(vec![Issue::NeverZero], diagnose(&[]).issues);
+
+        let history: Vec<i64> = (0..130).map(|i| if i % 2 == 0 { 1 } else { -1 }).collect();
+        assert_eq!(vec![Issue::Overflow], diagnose(&history).issues);
+    }
+
+    #[test]
+    fn test_diagnose_lines() {
+        let input = [
+            "0 3 6 9 12 15",
+            "1 3 6 10",
+            "1 2 4 8 16 32",
+            "10 13 16 21 30 45",
+        ];
+        let issues: Vec<Vec<Issue>> = diagnose_lines(input)
+            .into_iter()
+            .map(|diagnostics| diagnostics.issues)
+            .collect();
+        assert_eq!(
+            vec![
+                vec![],
+                vec![Issue::Length {
+                    expected: 6,
+                    found: 4
+                }],
+                vec![Issue::NeverZero],
+                vec![],
+            ],
+            issues
+        );
+    }
+
+    #[test]
+    fn test_polynomials_match_input() {
+        let input = include_str!("../input.txt");
+        let matrix = parse_matrix(input.lines());
+        for (history, diagnostics) in matrix.iter().zip(diagnose_lines(input.lines())) {
+            assert!(diagnostics.issues.is_empty());
+            let polynomial = diagnostics.polynomial.unwrap();
+            assert_eq!(diagnostics.degree, Some(polynomial.degree()));
+            for x in -3..history.len() as i64 + 3 {
+                assert_eq!(evaluate(history, x).ok(), polynomial.eval(x));
+            }
+        }
+    }
 }
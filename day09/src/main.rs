@@ -1,5 +1,6 @@
-use day9::{solution1, solution2};
+use day9::{diagnose_lines, solution1, solution2};
 
+use std::env;
 use std::io::{self, Read};
 
 fn main() {
@@ -7,6 +8,28 @@ fn main() {
     let mut input: String = String::new();
     stdin.lock().read_to_string(&mut input).unwrap();
 
+    let diagnostics = diagnose_lines(input.lines());
+
+    if env::args().nth(1).as_deref() == Some("diagnose") {
+        for (i, line) in diagnostics.iter().enumerate() {
+            match &line.polynomial {
+                Some(polynomial) => println!("{}: {polynomial}", i + 1),
+                None => println!("{}: ?", i + 1),
+            }
+            for issue in &line.issues {
+                println!("    {issue}");
+            }
+        }
+        return;
+    }
+
+    // The answers assume nothing's wrong with any line, so say if something is.
+    for (i, line) in diagnostics.iter().enumerate() {
+        for issue in &line.issues {
+            eprintln!("warning: line {} {issue}", i + 1);
+        }
+    }
+
     let p1 = solution1(input.lines());
     let p2 = solution2(input.lines());
     println!("Part 1: {p1}");
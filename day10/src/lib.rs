@@ -0,0 +1,829 @@
+use std::fmt::{self, Write};
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Dir {
+    North,
+    South,
+    East,
+    West,
+}
+
+impl Dir {
+    pub const ALL: [Dir; 4] = [Dir::North, Dir::South, Dir::East, Dir::West];
+
+    pub fn opposite(self) -> Self {
+        match self {
+            Self::North => Self::South,
+            Self::East => Self::West,
+            Self::South => Self::North,
+            Self::West => Self::East,
+        }
+    }
+
+    fn step(self, (row, col): (usize, usize)) -> (i32, i32) {
+        let row: i32 = row.try_into().unwrap();
+        let col: i32 = col.try_into().unwrap();
+        match self {
+            Self::North => (row - 1, col),
+            Self::East => (row, col + 1),
+            Self::South => (row + 1, col),
+            Self::West => (row, col - 1),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Tile {
+    Vertical,
+    Horizontal,
+    NorthEast,
+    NorthWest,
+    SouthWest,
+    SouthEast,
+    Ground,
+    Start,
+}
+
+impl Tile {
+    pub fn outgoing(self, incoming: Dir) -> Option<Dir> {
+        Some(match (self, incoming) {
+            (Self::Vertical, Dir::North) => Dir::South,
+            (Self::Vertical, Dir::South) => Dir::North,
+            (Self::Horizontal, Dir::West) => Dir::East,
+            (Self::Horizontal, Dir::East) => Dir::West,
+            (Self::NorthEast, Dir::North) => Dir::East,
+            (Self::NorthEast, Dir::East) => Dir::North,
+            (Self::NorthWest, Dir::North) => Dir::West,
+            (Self::NorthWest, Dir::West) => Dir::North,
+            (Self::SouthWest, Dir::South) => Dir::West,
+            (Self::SouthWest, Dir::West) => Dir::South,
+            (Self::SouthEast, Dir::South) => Dir::East,
+            (Self::SouthEast, Dir::East) => Dir::South,
+            _ => return None,
+        })
+    }
+
+    pub fn connects(self, incoming: Dir) -> bool {
+        self.outgoing(incoming).is_some()
+    }
+
+    /// The pipe joining `a` and `b`, in either order.
+    pub fn joining(a: Dir, b: Dir) -> Option<Self> {
+        Some(match (a, b) {
+            (Dir::North, Dir::South) | (Dir::South, Dir::North) => Self::Vertical,
+            (Dir::East, Dir::West) | (Dir::West, Dir::East) => Self::Horizontal,
+            (Dir::North, Dir::East) | (Dir::East, Dir::North) => Self::NorthEast,
+            (Dir::North, Dir::West) | (Dir::West, Dir::North) => Self::NorthWest,
+            (Dir::South, Dir::East) | (Dir::East, Dir::South) => Self::SouthEast,
+            (Dir::South, Dir::West) | (Dir::West, Dir::South) => Self::SouthWest,
+            _ => return None,
+        })
+    }
+
+    /// Whether the pipe turns a corner.
+    pub fn is_corner(self) -> bool {
+        matches!(
+            self,
+            Self::NorthEast | Self::NorthWest | Self::SouthWest | Self::SouthEast
+        )
+    }
+
+    pub fn from_char(c: char) -> Option<Self> {
+        Some(match c {
+            '|' | '│' => Self::Vertical,
+            '-' | '─' => Self::Horizontal,
+            'L' | '└' => Self::NorthEast,
+            'J' | '┘' => Self::NorthWest,
+            '7' | '┐' => Self::SouthWest,
+            'F' | '┌' => Self::SouthEast,
+            '.' => Self::Ground,
+            'S' => Self::Start,
+            _ => return None,
+        })
+    }
+
+    /// The tile drawn with box-drawing characters.
+    pub fn to_box_char(self) -> char {
+        match self {
+            Self::Vertical => '│',
+            Self::Horizontal => '─',
+            Self::NorthEast => '└',
+            Self::NorthWest => '┘',
+            Self::SouthWest => '┐',
+            Self::SouthEast => '┌',
+            Self::Ground => '.',
+            Self::Start => 'S',
+        }
+    }
+}
+
+/// Coordinates of every tile on a loop, starting with `start` and following the pipes until they
+/// lead back to it.
+pub struct LoopIterator<'a> {
+    tiles: &'a [Vec<Tile>],
+    start: (usize, usize),
+    curr: (usize, usize),
+    outgoing: Dir,
+    first: bool,
+}
+
+impl<'a> LoopIterator<'a> {
+    /// `tiles[start]` has to be a pipe rather than `Start`, and `outgoing` one of its ends.
+    pub fn new(tiles: &'a [Vec<Tile>], start: (usize, usize), outgoing: Dir) -> Self {
+        Self {
+            tiles,
+            start,
+            curr: start,
+            outgoing,
+            first: true,
+        }
+    }
+}
+
+impl<'a> Iterator for LoopIterator<'a> {
+    type Item = (usize, usize);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.first {
+            self.first = false;
+            return Some(self.start);
+        }
+
+        let (r, c) = self.outgoing.step(self.curr);
+        self.curr = (r.try_into().unwrap(), c.try_into().unwrap());
+
+        self.outgoing = self.tiles[self.curr.0][self.curr.1]
+            .outgoing(self.outgoing.opposite())
+            .unwrap();
+
+        if self.curr == self.start {
+            None
+        } else {
+            Some(self.curr)
+        }
+    }
+}
+
+/// Reads either the puzzle's ASCII pipes or the box-drawing ones from `PipeMaze::render`,
+/// skipping any colour codes.
+pub fn parse_tiles<'a>(lines: impl IntoIterator<Item = &'a str>) -> Vec<Vec<Tile>> {
+    lines
+        .into_iter()
+        .map(|line| {
+            let mut chars = line.chars();
+            let mut row = vec![];
+            while let Some(c) = chars.next() {
+                if c == '\x1b' {
+                    chars.find(|&c| c == 'm');
+                    continue;
+                }
+                row.push(Tile::from_char(c).unwrap());
+            }
+            row
+        })
+        .collect()
+}
+
+fn find_start(tiles: &[Vec<Tile>]) -> Option<(usize, usize)> {
+    for (row_idx, row) in tiles.iter().enumerate() {
+        for (col_idx, tile) in row.iter().enumerate() {
+            if *tile == Tile::Start {
+                return Some((row_idx, col_idx));
+            }
+        }
+    }
+    None
+}
+
+/// The neighbour of `pos` towards `dir`, if it's on the grid.
+fn neighbour(tiles: &[Vec<Tile>], pos: (usize, usize), dir: Dir) -> Option<(usize, usize)> {
+    let (row, col) = dir.step(pos);
+    let row = usize::try_from(row).ok()?;
+    let col = usize::try_from(col).ok()?;
+    tiles.get(row)?.get(col)?;
+    Some((row, col))
+}
+
+/// Follows the pipe leaving `start` towards `outgoing`, and returns the direction it comes back
+/// into `start` from, if it does come back.
+fn trace(tiles: &[Vec<Tile>], start: (usize, usize), mut outgoing: Dir) -> Option<Dir> {
+    let mut curr = start;
+    // Every tile has two ends, so the path can't loop without passing back through `start`.
+    loop {
+        curr = neighbour(tiles, curr, outgoing)?;
+        if curr == start {
+            return Some(outgoing.opposite());
+        }
+        outgoing = tiles[curr.0][curr.1].outgoing(outgoing.opposite())?;
+    }
+}
+
+// Replace start with appropriate tile.
+fn replace_start(tiles: &mut [Vec<Tile>], start: (usize, usize)) -> Result<(), MazeError> {
+    let connections: Vec<Dir> = Dir::ALL
+        .into_iter()
+        .filter(|&outgoing| {
+            neighbour(tiles, start, outgoing)
+                .is_some_and(|(row, col)| tiles[row][col].connects(outgoing.opposite()))
+        })
+        .collect();
+
+    // With more than two connections some of them are dead ends or belong to a second loop, so
+    // take the first which actually makes it round.
+    let tile = connections
+        .iter()
+        .find_map(|&outgoing| {
+            let incoming = trace(tiles, start, outgoing)?;
+            Tile::joining(outgoing, incoming)
+        })
+        .ok_or(MazeError::NoLoop)?;
+
+    tiles[start.0][start.1] = tile;
+    Ok(())
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MazeError {
+    NoStart,
+    /// None of the pipes leading out of the start lead back into it.
+    NoLoop,
+}
+
+impl fmt::Display for MazeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NoStart => write!(f, "no start tile"),
+            Self::NoLoop => write!(f, "the start isn't on a loop"),
+        }
+    }
+}
+
+impl std::error::Error for MazeError {}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Region {
+    Loop,
+    Inside,
+    Outside,
+}
+
+impl Region {
+    pub fn to_char(self) -> char {
+        match self {
+            Self::Loop => '#',
+            Self::Inside => 'I',
+            Self::Outside => 'O',
+        }
+    }
+}
+
+/// A classification as text, one line per row, with `#` for the loop and `I` and `O` for tiles
+/// inside and outside of it.
+pub fn region_map(regions: &[Vec<Region>]) -> String {
+    regions
+        .iter()
+        .map(|row| {
+            row.iter()
+                .map(|region| region.to_char())
+                .collect::<String>()
+                + "\n"
+        })
+        .collect()
+}
+
+const INSIDE_COLOUR: &str = "\x1b[32m";
+const OUTSIDE_COLOUR: &str = "\x1b[34m";
+const RESET: &str = "\x1b[0m";
+
+#[derive(Debug, Default, Clone, Copy)]
+pub struct RenderOptions {
+    /// Draw everything which isn't part of the main loop as ground.
+    pub loop_only: bool,
+    /// Colour tiles inside the loop green and those outside blue.
+    pub colour: bool,
+}
+
+/// A grid of pipes with the start tile replaced by the pipe that closes its loop.
+#[derive(Debug, Clone)]
+pub struct PipeMaze {
+    tiles: Vec<Vec<Tile>>,
+    start: (usize, usize),
+}
+
+impl PipeMaze {
+    pub fn new(mut tiles: Vec<Vec<Tile>>) -> Result<Self, MazeError> {
+        let start = find_start(&tiles).ok_or(MazeError::NoStart)?;
+        replace_start(&mut tiles, start)?;
+        Ok(Self { tiles, start })
+    }
+
+    pub fn parse<'a>(lines: impl IntoIterator<Item = &'a str>) -> Result<Self, MazeError> {
+        Self::new(parse_tiles(lines))
+    }
+
+    pub fn tiles(&self) -> &[Vec<Tile>] {
+        &self.tiles
+    }
+
+    pub fn start(&self) -> (usize, usize) {
+        self.start
+    }
+
+    /// The loop through the start, beginning with the start itself.
+    pub fn main_loop(&self) -> LoopIterator<'_> {
+        let (srow, scol) = self.start;
+        let outgoing = [Dir::North, Dir::East, Dir::South, Dir::West]
+            .into_iter()
+            .find(|&outgoing| self.tiles[srow][scol].connects(outgoing))
+            .unwrap();
+        LoopIterator::new(&self.tiles, self.start, outgoing)
+    }
+
+    /// Whether each tile is on the main loop.
+    pub fn loop_mask(&self) -> Vec<Vec<bool>> {
+        let mut mask: Vec<Vec<bool>> = self
+            .tiles
+            .iter()
+            .map(|row| vec![false; row.len()])
+            .collect();
+        for (row, col) in self.main_loop() {
+            mask[row][col] = true;
+        }
+        mask
+    }
+
+    /// Steps along the loop to the tile farthest from the start.
+    pub fn farthest(&self) -> usize {
+        self.main_loop().count() / 2
+    }
+
+    /// The loop as a polygon through the centres of its corner tiles, in the order they're
+    /// walked.
+    pub fn polygon(&self) -> Vec<(usize, usize)> {
+        self.main_loop()
+            .filter(|&(row, col)| self.tiles[row][col].is_corner())
+            .collect()
+    }
+
+    /// Number of tiles enclosed by the loop, found by casting a ray along each row.
+    pub fn enclosed_ray_cast(&self) -> usize {
+        let on_loop = self.loop_mask();
+
+        // To find interior points, we cast a ray from the left edge of the board to the right,
+        // keeping track of the number of times we intersect with the loop. A non-loop point is
+        // inside the loop if and only if the number of intersections is odd.
+        //
+        // There's a literal edge case when we encounter a horizontal edge. We bias our ray to
+        // the "upper half" of a tile so that we only intersect with Vertical, NorthEast, and
+        // NorthWest tiles.
+        let mut enclosed = 0;
+        for (row, on_loop) in self.tiles.iter().zip(&on_loop) {
+            let mut intersections = 0;
+            for (tile, &on_loop) in row.iter().zip(on_loop) {
+                match tile {
+                    Tile::Vertical | Tile::NorthEast | Tile::NorthWest if on_loop => {
+                        intersections += 1;
+                    }
+                    _ if !on_loop && intersections % 2 == 1 => enclosed += 1,
+                    _ => {}
+                }
+            }
+        }
+        enclosed
+    }
+
+    /// Number of tiles enclosed by the loop, from the polygon's area by the shoelace formula and
+    /// Pick's theorem, `area = interior + boundary / 2 - 1`.
+    pub fn enclosed_pick(&self) -> usize {
+        let polygon = self.polygon();
+        let twice_area: i64 = polygon
+            .iter()
+            .zip(polygon.iter().cycle().skip(1))
+            .map(|(&(r1, c1), &(r2, c2))| {
+                let [r1, c1, r2, c2] = [r1, c1, r2, c2].map(|x| i64::try_from(x).unwrap());
+                c1 * r2 - c2 * r1
+            })
+            .sum::<i64>()
+            .abs();
+        let boundary = i64::try_from(self.main_loop().count()).unwrap();
+
+        usize::try_from((twice_area - boundary + 2) / 2).unwrap()
+    }
+
+    /// Classifies every tile by flood filling the outside of the loop on a grid with twice the
+    /// resolution, where the gaps between tiles are cells of their own. That lets the fill
+    /// squeeze between pipes which touch without being joined.
+    pub fn classify(&self) -> Vec<Vec<Region>> {
+        let on_loop = self.loop_mask();
+        let rows = self.tiles.len();
+        let cols = self.tiles.iter().map(Vec::len).max().unwrap_or(0);
+
+        // Tile (row, col) sits at (2 * row + 1, 2 * col + 1), leaving a free border all the
+        // way round for the fill to start from.
+        let (height, width) = (2 * rows + 1, 2 * cols + 1);
+        let mut blocked = vec![vec![false; width]; height];
+        for (row, tiles) in self.tiles.iter().enumerate() {
+            for (col, &tile) in tiles.iter().enumerate() {
+                if !on_loop[row][col] {
+                    continue;
+                }
+                let (r, c) = (2 * row + 1, 2 * col + 1);
+                blocked[r][c] = true;
+                if tile.connects(Dir::East) {
+                    blocked[r][c + 1] = true;
+                }
+                if tile.connects(Dir::South) {
+                    blocked[r + 1][c] = true;
+                }
+            }
+        }
+
+        let mut outside = vec![vec![false; width]; height];
+        let mut stack: Vec<(usize, usize)> = vec![(0, 0)];
+        outside[0][0] = true;
+        while let Some((r, c)) = stack.pop() {
+            let neighbours = [
+                (r.wrapping_sub(1), c),
+                (r + 1, c),
+                (r, c.wrapping_sub(1)),
+                (r, c + 1),
+            ];
+            for (r, c) in neighbours {
+                if r < height && c < width && !blocked[r][c] && !outside[r][c] {
+                    outside[r][c] = true;
+                    stack.push((r, c));
+                }
+            }
+        }
+
+        self.tiles
+            .iter()
+            .enumerate()
+            .map(|(row, tiles)| {
+                (0..tiles.len())
+                    .map(|col| {
+                        if on_loop[row][col] {
+                            Region::Loop
+                        } else if outside[2 * row + 1][2 * col + 1] {
+                            Region::Outside
+                        } else {
+                            Region::Inside
+                        }
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+
+    /// Number of tiles enclosed by the loop, found by flood fill.
+    pub fn enclosed_flood_fill(&self) -> usize {
+        self.classify()
+            .iter()
+            .flatten()
+            .filter(|&&region| region == Region::Inside)
+            .count()
+    }
+
+    /// The maze drawn with box-drawing characters, with the start shown as `S` so that it
+    /// parses back into the same maze.
+    pub fn render(&self, options: RenderOptions) -> String {
+        let regions = self.classify();
+        let mut out = String::new();
+        for (row, tiles) in self.tiles.iter().enumerate() {
+            for (col, &tile) in tiles.iter().enumerate() {
+                let region = regions[row][col];
+                let tile = if (row, col) == self.start {
+                    Tile::Start
+                } else if options.loop_only && region != Region::Loop {
+                    Tile::Ground
+                } else {
+                    tile
+                };
+
+                match region {
+                    Region::Inside if options.colour => {
+                        write!(out, "{INSIDE_COLOUR}{}{RESET}", tile.to_box_char()).unwrap();
+                    }
+                    Region::Outside if options.colour => {
+                        write!(out, "{OUTSIDE_COLOUR}{}{RESET}", tile.to_box_char()).unwrap();
+                    }
+                    _ => out.push(tile.to_box_char()),
+                }
+            }
+            out.push('\n');
+        }
+        out
+    }
+}
+
+pub fn solution<'a>(lines: impl IntoIterator<Item = &'a str>) -> (usize, usize) {
+    let maze = PipeMaze::parse(lines).unwrap();
+
+    let p1 = maze.farthest();
+    let p2 = maze.enclosed_ray_cast();
+    debug_assert_eq!(p2, maze.enclosed_pick());
+    debug_assert_eq!(p2, maze.enclosed_flood_fill());
+
+    (p1, p2)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    #[test]
+    fn part_1() {
+        let input = include_str!("../input.txt").lines();
+        let (p1, _) = solution(input);
+        assert_eq!(6867, p1);
+    }
+
+    #[test]
+    fn part_2() {
+        let input = include_str!("../input.txt").lines();
+        let (_, p2) = solution(input);
+        assert_eq!(595, p2);
+    }
+
+    #[test]
+    fn test_pick_matches_ray_cast() {
+        let maze = PipeMaze::parse(include_str!("../input.txt").lines()).unwrap();
+        assert_eq!(595, maze.enclosed_pick());
+    }
+
+    #[test]
+    fn test_example() {
+        let maze = PipeMaze::parse(include_str!("../example.txt").lines()).unwrap();
+        assert_eq!((1, 1), maze.start());
+        assert_eq!(Tile::SouthEast, maze.tiles()[1][1]);
+        assert_eq!(23, maze.farthest());
+        assert_eq!(4, maze.enclosed_ray_cast());
+        assert_eq!(4, maze.enclosed_pick());
+        assert_eq!(
+            vec![
+                (1, 1),
+                (1, 9),
+                (7, 9),
+                (7, 6),
+                (5, 6),
+                (5, 8),
+                (2, 8),
+                (2, 2),
+                (5, 2),
+                (5, 4),
+                (7, 4),
+                (7, 1)
+            ],
+            maze.polygon()
+        );
+    }
+
+    #[test]
+    fn test_start_with_extra_connections() {
+        // S has four pipes pointing into it, but only the ones to the south and east make a
+        // loop. The northern one leads off the edge and the western one into the ground.
+        let input = [
+            "..|..", //
+            ".-S-7", //
+            "..|.|", //
+            "..L-J", //
+        ];
+        let maze = PipeMaze::parse(input).unwrap();
+        assert_eq!(Tile::SouthEast, maze.tiles()[1][2]);
+        assert_eq!(4, maze.farthest());
+        assert_eq!(1, maze.enclosed_ray_cast());
+        assert_eq!(1, maze.enclosed_pick());
+
+        // Two loops meet at S, and the northern one is found first.
+        let input = [
+            "F-7", //
+            "|.|", //
+            "S-J", //
+            "|.|", //
+            "L-J", //
+        ];
+        let maze = PipeMaze::parse(input).unwrap();
+        assert_eq!(Tile::NorthEast, maze.tiles()[2][0]);
+        assert_eq!(vec![(2, 0), (0, 0), (0, 2), (2, 2)], maze.polygon());
+    }
+
+    #[test]
+    fn test_no_loop() {
+        assert_eq!(
+            MazeError::NoLoop,
+            PipeMaze::parse(["S-7", "|.."]).unwrap_err()
+        );
+        assert_eq!(
+            MazeError::NoStart,
+            PipeMaze::parse(["F7", "LJ"]).unwrap_err()
+        );
+    }
+
+    #[test]
+    fn test_loop_iterator() {
+        let maze = PipeMaze::parse(["S7", "LJ"]).unwrap();
+        let coords: Vec<(usize, usize)> = maze.main_loop().collect();
+        assert_eq!(vec![(0, 0), (0, 1), (1, 1), (1, 0)], coords);
+        assert_eq!(coords, maze.polygon());
+        assert_eq!(0, maze.enclosed_pick());
+    }
+
+    #[test]
+    fn test_region_map() {
+        let maze = PipeMaze::parse(include_str!("../example.txt").lines()).unwrap();
+        let expected = "\
+OOOOOOOOOOO
+O#########O
+O#########O
+O##OOOOO##O
+O##OOOOO##O
+O####O####O
+O#II#O#II#O
+O####O####O
+OOOOOOOOOOO
+";
+        assert_eq!(expected, region_map(&maze.classify()));
+        assert_eq!(4, maze.enclosed_flood_fill());
+
+        // The outside squeezes down between the two pipes in the middle.
+        let input = [
+            "..........",
+            ".S------7.",
+            ".|F----7|.",
+            ".||....||.",
+            ".||....||.",
+            ".|L-7F-J|.",
+            ".|..||..|.",
+            ".L--JL--J.",
+            "..........",
+        ];
+        let maze = PipeMaze::parse(input).unwrap();
+        let expected = "\
+OOOOOOOOOO
+O########O
+O########O
+O##OOOO##O
+O##OOOO##O
+O########O
+O#II##II#O
+O########O
+OOOOOOOOOO
+";
+        assert_eq!(expected, region_map(&maze.classify()));
+        assert_eq!(4, maze.enclosed_flood_fill());
+        assert_eq!(4, maze.enclosed_ray_cast());
+    }
+
+    /// Pseudo-random numbers for generating mazes, from a linear congruential generator.
+    struct Lcg(u64);
+
+    impl Lcg {
+        fn below(&mut self, n: usize) -> usize {
+            self.0 = self
+                .0
+                .wrapping_mul(6_364_136_223_846_793_005)
+                .wrapping_add(1_442_695_040_888_963_407);
+            ((self.0 >> 33) % n as u64) as usize
+        }
+    }
+
+    /// A maze whose loop runs round a random stack of columns, with junk pipes everywhere else.
+    fn generate_maze(rng: &mut Lcg, rows: usize, cols: usize) -> Vec<Vec<Tile>> {
+        // Cells sit between tiles, so cell (r, c) has tiles at its corners (r, c) to
+        // (r + 1, c + 1). Each column of cells overlaps its neighbours so the loop never
+        // pinches.
+        let width = 1 + rng.below(cols - 1);
+        let mut spans: Vec<(usize, usize)> = vec![];
+        for _ in 0..width {
+            let span = loop {
+                let top = rng.below(rows - 1);
+                let bottom = top + 1 + rng.below(rows - 1 - top);
+                match spans.last() {
+                    Some(&(prev_top, prev_bottom)) if top >= prev_bottom || bottom <= prev_top => {}
+                    _ => break (top, bottom),
+                }
+            };
+            spans.push(span);
+        }
+        let left = rng.below(cols - width);
+        let in_region = |r: usize, c: usize| {
+            c >= left && c - left < width && (spans[c - left].0..spans[c - left].1).contains(&r)
+        };
+        // The edge of the region between two tiles is a pipe when exactly one of the cells
+        // either side of it is in the region.
+        let cell = |r: usize, c: usize, dr: isize, dc: isize| {
+            let (r, c) = (r as isize + dr, c as isize + dc);
+            r >= 0 && c >= 0 && in_region(r as usize, c as usize)
+        };
+
+        let junk = [
+            Tile::Vertical,
+            Tile::Horizontal,
+            Tile::NorthEast,
+            Tile::NorthWest,
+            Tile::SouthWest,
+            Tile::SouthEast,
+            Tile::Ground,
+        ];
+        let mut tiles = vec![vec![Tile::Ground; cols]; rows];
+        let mut on_loop = vec![];
+        for (r, row) in tiles.iter_mut().enumerate() {
+            for (c, tile) in row.iter_mut().enumerate() {
+                let [nw, ne, sw, se] =
+                    [(-1, -1), (-1, 0), (0, -1), (0, 0)].map(|(dr, dc)| cell(r, c, dr, dc));
+                let ends: Vec<Dir> = [
+                    (Dir::North, nw != ne),
+                    (Dir::South, sw != se),
+                    (Dir::East, ne != se),
+                    (Dir::West, nw != sw),
+                ]
+                .into_iter()
+                .filter_map(|(dir, is_edge)| is_edge.then_some(dir))
+                .collect();
+                *tile = match ends[..] {
+                    [a, b] => {
+                        on_loop.push((r, c));
+                        Tile::joining(a, b).unwrap()
+                    }
+                    _ => junk[rng.below(junk.len())],
+                };
+            }
+        }
+
+        let (r, c) = on_loop[rng.below(on_loop.len())];
+        tiles[r][c] = Tile::Start;
+        tiles
+    }
+
+    #[test]
+    fn test_generated_mazes() {
+        let mut rng = Lcg(10);
+        for _ in 0..300 {
+            let (rows, cols) = (2 + rng.below(15), 2 + rng.below(15));
+            let maze = PipeMaze::new(generate_maze(&mut rng, rows, cols)).unwrap();
+            let ray_cast = maze.enclosed_ray_cast();
+            assert_eq!(ray_cast, maze.enclosed_flood_fill(), "{maze:?}");
+            assert_eq!(ray_cast, maze.enclosed_pick(), "{maze:?}");
+        }
+    }
+
+    #[test]
+    fn test_render() {
+        let input = ["-L|F7", "7S-7|", "L|7||", "-L-J|", "L|-JF"];
+        let maze = PipeMaze::parse(input).unwrap();
+        let expected = "\
+─└│┌┐
+┐S─┐│
+└│┐││
+─└─┘│
+└│─┘┌
+";
+        assert_eq!(expected, maze.render(RenderOptions::default()));
+
+        let options = RenderOptions {
+            loop_only: true,
+            ..RenderOptions::default()
+        };
+        let expected = "\
+.....
+.S─┐.
+.│.│.
+.└─┘.
+.....
+";
+        assert_eq!(expected, maze.render(options));
+
+        let options = RenderOptions {
+            loop_only: true,
+            colour: true,
+        };
+        let rendered = maze.render(options);
+        assert!(rendered.starts_with("\x1b[34m.\x1b[0m"));
+        assert!(rendered.contains("│\x1b[32m.\x1b[0m│"));
+    }
+
+    #[test]
+    fn test_render_round_trip() {
+        let mut rng = Lcg(49);
+        let mut mazes = vec![PipeMaze::parse(include_str!("../input.txt").lines()).unwrap()];
+        for _ in 0..50 {
+            let (rows, cols) = (2 + rng.below(15), 2 + rng.below(15));
+            mazes.push(PipeMaze::new(generate_maze(&mut rng, rows, cols)).unwrap());
+        }
+
+        for maze in mazes {
+            for loop_only in [false, true] {
+                for colour in [false, true] {
+                    let options = RenderOptions { loop_only, colour };
+                    let rendered = maze.render(options);
+                    let parsed = PipeMaze::parse(rendered.lines()).unwrap();
+                    assert_eq!(maze.start(), parsed.start());
+                    assert_eq!(maze.classify(), parsed.classify());
+                    if !loop_only {
+                        assert_eq!(maze.tiles(), parsed.tiles());
+                    }
+                }
+            }
+        }
+    }
+}
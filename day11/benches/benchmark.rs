@@ -0,0 +1,63 @@
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use day11::{solution, solution_pairwise};
+
+/// Pseudo-random numbers for generating universes, from a linear congruential generator.
+struct Lcg(u64);
+
+impl Lcg {
+    fn below(&mut self, n: usize) -> usize {
+        self.0 = self
+            .0
+            .wrapping_mul(6_364_136_223_846_793_005)
+            .wrapping_add(1_442_695_040_888_963_407);
+        ((self.0 >> 33) % n as u64) as usize
+    }
+}
+
+/// A square universe of side `size` in which roughly one tile in `sparsity` is a galaxy, with a
+/// few empty rows and columns left in.
+fn generate_universe(rng: &mut Lcg, size: usize, sparsity: usize) -> String {
+    let mut text = String::new();
+    for row in 0..size {
+        for col in 0..size {
+            let galaxy = row % 7 != 3 && col % 11 != 5 && rng.below(sparsity) == 0;
+            text.push(if galaxy { '#' } else { '.' });
+        }
+        text.push('\n');
+    }
+    text
+}
+
+pub fn criterion_benchmark(c: &mut Criterion) {
+    let text = include_str!("../input.txt");
+    let mut group = c.benchmark_group("input");
+    group.bench_function("pairwise", |b| {
+        b.iter(|| solution_pairwise(text.lines(), 1_000_000))
+    });
+    group.bench_function("prefix sums", |b| {
+        b.iter(|| solution(text.lines(), 1_000_000))
+    });
+    group.finish();
+
+    let mut group = c.benchmark_group("generated");
+    group.sample_size(10);
+    for size in [200, 500] {
+        let text = generate_universe(&mut Lcg(11), size, 10);
+        group.bench_with_input(BenchmarkId::new("pairwise", size), &text, |b, text| {
+            b.iter(|| solution_pairwise(text.lines(), 1_000_000))
+        });
+        group.bench_with_input(BenchmarkId::new("prefix sums", size), &text, |b, text| {
+            b.iter(|| solution(text.lines(), 1_000_000))
+        });
+    }
+
+    // Millions of galaxies, far too many to compare pairwise.
+    let text = generate_universe(&mut Lcg(11), 5000, 10);
+    group.bench_function("prefix sums/5000", |b| {
+        b.iter(|| solution(text.lines(), 1_000_000))
+    });
+    group.finish();
+}
+
+criterion_group!(benches, criterion_benchmark);
+criterion_main!(benches);
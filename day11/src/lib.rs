@@ -0,0 +1,164 @@
+fn parse_universe<'a>(lines: impl IntoIterator<Item = &'a str>) -> Vec<Vec<u8>> {
+    lines
+        .into_iter()
+        .map(|line| line.as_bytes().to_vec())
+        .collect()
+}
+
+/// Compares every pair of galaxies after moving each one past the empty rows and columns before
+/// it, which is quadratic in the number of galaxies.
+pub fn solution_pairwise<'a>(lines: impl IntoIterator<Item = &'a str>, scale: usize) -> usize {
+    let universe = parse_universe(lines);
+    let width = universe[0].len();
+
+    let mut galaxies: Vec<(usize, usize)> = Vec::new();
+    for (row_idx, row) in universe.iter().enumerate() {
+        for (col_idx, tile) in row.iter().enumerate() {
+            if *tile != b'.' {
+                galaxies.push((row_idx, col_idx));
+            }
+        }
+    }
+
+    let mut empty_rows: Vec<usize> = Vec::new();
+    for (row_idx, row) in universe.iter().enumerate() {
+        if row.iter().all(|&tile| tile == b'.') {
+            empty_rows.push(row_idx);
+        }
+    }
+
+    let mut empty_cols: Vec<usize> = Vec::new();
+    for col_idx in 0..width {
+        if universe.iter().all(|row| row[col_idx] == b'.') {
+            empty_cols.push(col_idx);
+        }
+    }
+
+    for galaxy in &mut galaxies {
+        for row in empty_rows.iter().rev() {
+            if galaxy.0 > *row {
+                galaxy.0 += scale - 1;
+            }
+        }
+        for col in empty_cols.iter().rev() {
+            if galaxy.1 > *col {
+                galaxy.1 += scale - 1;
+            }
+        }
+    }
+
+    let mut total = 0;
+    for (i, g1) in galaxies.iter().enumerate() {
+        for g2 in &galaxies[i + 1..] {
+            total += g1.0.abs_diff(g2.0) + g1.1.abs_diff(g2.1);
+        }
+    }
+
+    total
+}
+
+fn find_galaxies(universe: &[Vec<u8>]) -> Vec<(usize, usize)> {
+    universe
+        .iter()
+        .enumerate()
+        .flat_map(|(row_idx, row)| {
+            row.iter()
+                .enumerate()
+                .filter(|&(_, &tile)| tile != b'.')
+                .map(move |(col_idx, _)| (row_idx, col_idx))
+        })
+        .collect()
+}
+
+/// Where each of `coords` ends up along an axis of length `len` once every position without a
+/// galaxy has grown to `scale` wide. A large enough `scale` pushes them past `usize::MAX`.
+fn expand(coords: &[usize], len: usize, scale: usize) -> Vec<u128> {
+    let mut occupied = vec![false; len];
+    for &coord in coords {
+        occupied[coord] = true;
+    }
+
+    // Number of empty positions before each one.
+    let mut empties_before = Vec::with_capacity(len);
+    let mut empties: u128 = 0;
+    for &occupied in &occupied {
+        empties_before.push(empties);
+        if !occupied {
+            empties += 1;
+        }
+    }
+
+    coords
+        .iter()
+        .map(|&coord| coord as u128 + (scale as u128 - 1) * empties_before[coord])
+        .collect()
+}
+
+/// Sum of the distances between every pair of `coords`. Once they're sorted, the `i`th
+/// coordinate is added once for each of the `i` before it and those are subtracted, so a running
+/// total of the ones before is all that's needed.
+pub fn pairwise_distance_sum(mut coords: Vec<u128>) -> u128 {
+    coords.sort_unstable();
+
+    let mut before: u128 = 0;
+    let mut total: u128 = 0;
+    for (i, &coord) in coords.iter().enumerate() {
+        total += coord * i as u128 - before;
+        before += coord;
+    }
+    total
+}
+
+/// Manhattan distances split into one sum per axis, each done by `pairwise_distance_sum` in
+/// O(n log n).
+pub fn solution<'a>(lines: impl IntoIterator<Item = &'a str>, scale: usize) -> u128 {
+    let universe = parse_universe(lines);
+    let height = universe.len();
+    let width = universe[0].len();
+
+    let (rows, cols): (Vec<usize>, Vec<usize>) = find_galaxies(&universe).into_iter().unzip();
+    pairwise_distance_sum(expand(&rows, height, scale))
+        + pairwise_distance_sum(expand(&cols, width, scale))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    #[test]
+    fn test_example() {
+        let input = include_str!("../ex1.txt");
+        for (scale, expected) in [(2, 374), (10, 1030), (100, 8410)] {
+            assert_eq!(expected, solution(input.lines(), scale));
+            assert_eq!(expected as usize, solution_pairwise(input.lines(), scale));
+        }
+    }
+
+    #[test]
+    fn test_input() {
+        let input = include_str!("../input.txt");
+        assert_eq!(9918828, solution(input.lines(), 2));
+        assert_eq!(692506533832, solution(input.lines(), 1_000_000));
+        assert_eq!(692506533832, solution_pairwise(input.lines(), 1_000_000));
+    }
+
+    #[test]
+    fn test_pairwise_distance_sum() {
+        assert_eq!(0, pairwise_distance_sum(vec![]));
+        assert_eq!(0, pairwise_distance_sum(vec![5, 5]));
+        // 1 + 3 + 6 + 2 + 5 + 3
+        assert_eq!(20, pairwise_distance_sum(vec![7, 1, 4, 2]));
+    }
+
+    #[test]
+    fn test_huge_scale() {
+        // One empty row and column between the galaxies.
+        let input = "#..\n...\n..#";
+        let scale = usize::MAX;
+        assert_eq!(vec![0, 2 + (scale as u128 - 1)], expand(&[0, 2], 3, scale));
+        assert_eq!(
+            2 * (2 + (scale as u128 - 1)),
+            solution(input.lines(), scale)
+        );
+    }
+}